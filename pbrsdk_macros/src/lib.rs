@@ -6,10 +6,16 @@
 //! - `id`
 //!
 //! Those properties are included in most responses of PocketBase.
+//!
+//! The generated code implements traits of the `pbrsdk` crate,
+//! so this macro can't be used without it.
+//! It is re-exported by `pbrsdk`, which is the recommended way to use it.
 
-use quote::{quote};
+use quote::{format_ident, quote};
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, Attribute, Field, Fields, ItemStruct};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Attribute, Field, Fields, Ident, ItemStruct, Path, Token, Type};
 
 /// Most requests to the PocketBase server will include 3 common properties in their response :
 ///
@@ -32,6 +38,11 @@ use syn::{parse_macro_input, Attribute, Field, Fields, ItemStruct};
 /// # Note
 ///
 /// This procedural macro automatically adds the attribute `#[derive(serde::Deserialize)]`
+/// and implements `pbrsdk::BaseSystemFields`, so that the struct can be given
+/// to the methods that need to locate the record, such as `pb.files().get_url()`.
+///
+/// The generated code refers to the `pbrsdk` crate as `::pbrsdk`.
+/// If the dependency is renamed, give its path with `#[base_system_fields(crate = path)]`.
 ///
 /// # Example
 ///
@@ -58,18 +69,75 @@ use syn::{parse_macro_input, Attribute, Field, Fields, ItemStruct};
 ///     println!("{}", article.collection_name);
 /// }
 /// ```
+///
+/// # Expanding relations
+///
+/// The relations to expand can be listed with the `#[expand(...)]` attribute,
+/// which must be placed below `#[base_system_fields]`.
+/// Each entry is the name of a relation field and the type of the related records,
+/// either a single record or a `Vec` of records.
+/// A record related to its own collection must be boxed (`parent: Box<Category>`).
+///
+/// It generates a struct named after the record (`ArticleExpand` for `Article`)
+/// holding a `pbrsdk::Relation` for each entry, and adds an `expand` field to the record.
+/// The `expand` query parameter is generated by `pbrsdk::Expandable::expand_query()`,
+/// including the relations of the related records (`author.company`), if they are expanded too,
+/// up to `pbrsdk::MAX_EXPAND_DEPTH` levels.
+/// The relations of a record that is already expanded above it (such as `parent.parent`) are left out.
+///
+/// ```
+/// use pbrsdk::*;
+///
+/// #[base_system_fields]
+/// struct Company {
+///     name: String,
+/// }
+///
+/// #[base_system_fields]
+/// #[expand(company: Company)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[base_system_fields]
+/// #[expand(author: User, tags: Vec<Company>)]
+/// struct Article {
+///     title: String,
+/// }
+///
+/// assert_eq!(Article::expand_query(), "author,author.company,tags");
+/// # let json = r#"{"id":"a","collectionId":"c","collectionName":"articles","title":"Hello","expand":{"author":{"id":"u","collectionId":"c","collectionName":"users","name":"John"}}}"#;
+/// # let article: Article = serde_json::from_str(json).unwrap();
+/// # assert_eq!(article.expand.author.get().unwrap().name, "John");
+/// # assert!(!article.expand.tags.is_expanded());
+/// ```
 #[proc_macro_attribute]
-pub fn base_system_fields(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn base_system_fields(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as MacroArgs);
+    let krate = args.krate;
     let mut input = parse_macro_input!(item as ItemStruct);
 
-    // Ensure named fields
-    let fields = match &mut input.fields {
-        Fields::Named(fields) => &mut fields.named,
-        _ => panic!("#[base_system_fields] only supports structs with named fields"),
-    };
+    // Extract the relations to expand
+    let mut relations: Vec<ExpandEntry> = Vec::new();
+    let mut remaining_attrs = Vec::new();
+    for attr in input.attrs.drain(..) {
+        if attr.path().is_ident("expand") {
+            match attr.parse_args_with(Punctuated::<ExpandEntry, Token![,]>::parse_terminated) {
+                Ok(entries) => relations.extend(entries),
+                Err(err) => return err.to_compile_error().into(),
+            }
+        } else {
+            remaining_attrs.push(attr);
+        }
+    }
+    input.attrs = remaining_attrs;
+
+    let name = input.ident.clone();
+    let vis = input.vis.clone();
+    let expand_name = format_ident!("{}Expand", name);
 
     // Fields to inject
-    let injected_fields: Vec<Field> = vec![
+    let mut injected_fields: Vec<Field> = vec![
         syn::parse_quote!(
             /// The ID of the fetched record.
             pub id: String
@@ -85,16 +153,141 @@ pub fn base_system_fields(_attr: TokenStream, item: TokenStream) -> TokenStream
             pub collection_name: String
         ),
     ];
+    if !relations.is_empty() {
+        injected_fields.push(syn::parse_quote!(
+            /// The expanded relations of the fetched record.
+            #[serde(default)]
+            pub expand: #krate::Expand<#expand_name>
+        ));
+    }
+
+    // Ensure named fields
+    let fields = match &mut input.fields {
+        Fields::Named(fields) => &mut fields.named,
+        _ => panic!("#[base_system_fields] only supports structs with named fields"),
+    };
 
     // Prepend injected fields
     for field in injected_fields.into_iter().rev() {
         fields.insert(0, field);
     }
 
+    // The derives of the record must also be satisfied by its expand struct.
+    let mut derives: Vec<Path> = Vec::new();
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("derive")) {
+        match attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated) {
+            Ok(paths) => derives.extend(paths.into_iter().filter(|x| {
+                x.segments.last().is_some_and(|x| x.ident != "Default" && x.ident != "Deserialize")
+            })),
+            Err(err) => return err.to_compile_error().into(),
+        }
+    }
+
     let serde_attr: Attribute = syn::parse_quote!(#[derive(serde::Deserialize)]);
     input.attrs.insert(0, serde_attr);
 
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let expand = if relations.is_empty() {
+        quote! {
+            impl #impl_generics #krate::Expandable for #name #ty_generics #where_clause {
+                fn expand_paths_within(_ancestors: &mut Vec<&'static str>) -> Vec<String> {
+                    Vec::new()
+                }
+            }
+        }
+    } else {
+        let relation_names: Vec<&Ident> = relations.iter().map(|x| &x.name).collect();
+        let relation_types: Vec<&Type> = relations.iter().map(|x| &x.ty).collect();
+        let doc = format!("The expanded relations of [{}].", name);
+        quote! {
+            #[doc = #doc]
+            #[derive(serde::Deserialize, Default, #(#derives),*)]
+            #vis struct #expand_name {
+                #(
+                    #[doc = concat!("The expanded `", stringify!(#relation_names), "` relation.")]
+                    #[serde(default)]
+                    pub #relation_names: #krate::Relation<#relation_types>,
+                )*
+            }
+
+            impl #krate::Expandable for #expand_name {
+                fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+                    let mut paths = Vec::new();
+                    #(
+                        paths.push(stringify!(#relation_names).to_string());
+                        for path in <#relation_types as #krate::Expandable>::expand_paths_within(ancestors) {
+                            paths.push(format!("{}.{}", stringify!(#relation_names), path));
+                        }
+                    )*
+                    paths
+                }
+            }
+
+            impl #impl_generics #krate::Expandable for #name #ty_generics #where_clause {
+                fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+                    let record = std::any::type_name::<Self>();
+                    if ancestors.len() >= #krate::MAX_EXPAND_DEPTH || ancestors.contains(&record) {
+                        return Vec::new();
+                    }
+                    ancestors.push(record);
+                    let paths = <#expand_name as #krate::Expandable>::expand_paths_within(ancestors);
+                    ancestors.pop();
+                    paths
+                }
+            }
+        }
+    };
+
     TokenStream::from(quote! {
         #input
+
+        impl #impl_generics #krate::BaseSystemFields for #name #ty_generics #where_clause {
+            fn id(&self) -> &str {
+                &self.id
+            }
+
+            fn collection_id(&self) -> &str {
+                &self.collection_id
+            }
+
+            fn collection_name(&self) -> &str {
+                &self.collection_name
+            }
+        }
+
+        #expand
     })
-}
\ No newline at end of file
+}
+
+/// The arguments of the macro: nothing, or `crate = path`.
+struct MacroArgs {
+    krate: Path,
+}
+
+impl Parse for MacroArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(MacroArgs { krate: syn::parse_quote!(::pbrsdk) });
+        }
+        input.parse::<Token![crate]>()?;
+        input.parse::<Token![=]>()?;
+        let krate = Path::parse_mod_style(input)?;
+        Ok(MacroArgs { krate })
+    }
+}
+
+/// An entry of the `#[expand(...)]` attribute: `name: Type`.
+struct ExpandEntry {
+    name: Ident,
+    ty: Type,
+}
+
+impl Parse for ExpandEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        Ok(ExpandEntry { name, ty })
+    }
+}
@@ -2,8 +2,11 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use pbrsdk_macros::base_system_fields;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::error::ApiError;
+use crate::datetime::PbDateTime;
+use crate::backends::AuthData;
+use crate::cookies::{cookie_auth_data, cookie_serialize, CookieOptions};
 
 /// If you don't want to bother changing the default 'users' collection of PocketBase,
 /// then use this struct that already contains all the columns
@@ -12,21 +15,27 @@ use crate::error::ApiError;
 /// # Note
 ///
 /// The `name` property is optional since '_superusers' doesn't have it.
-/// The `verified` and `email_visibility` properties default to `false`.
-#[base_system_fields]
+/// The `verified` and `email_visibility` properties default to `false`,
+/// and the dates to [Option::None], so that the record stripped down
+/// by [AuthStore::export_to_cookie] can still be loaded.
+#[base_system_fields(crate = crate)]
 #[derive(Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DefaultAuthRecord {
     /// `email` column.
     pub email: String,
     /// `verified` column, defaults to `false` if the collection is `_superusers`.
+    #[serde(default)]
     pub verified: bool,
     /// `email_visibility` column, defaults to `false` if the collection is `_superusers`.
+    #[serde(default)]
     pub email_visibility: bool,
-    /// `created` column as a string that is formatted according to the ISO standard of dates.
-    pub created: String,
-    /// `updated` column as a string that is formatted according to the ISO standard of dates.
-    pub updated: String,
+    /// `created` column.
+    #[serde(default, with = "crate::datetime::optional")]
+    pub created: Option<PbDateTime>,
+    /// `updated` column.
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated: Option<PbDateTime>,
     /// `name` column. Defaults to [Option::None] if the collection is `_superusers`.
     pub name: Option<String>, // it's optional because such column doesn't exist in the default _superusers collection
 }
@@ -46,6 +55,9 @@ where T: DeserializeOwned + Clone {
     pub collection_id: Option<String>,
     /// The ID of the user record.
     pub record_id: Option<String>,
+    /// The record as it was returned by the server,
+    /// which is what gets persisted by the [AuthStoreBackend](crate::AuthStoreBackend).
+    pub(crate) raw_record: Option<serde_json::Value>,
 }
 
 impl<T> Default for AuthStore<T>
@@ -57,6 +69,7 @@ where T: DeserializeOwned + Clone {
             collection_id: None,
             collection_name: None,
             record_id: None,
+            raw_record: None,
         }
     }
 }
@@ -69,6 +82,124 @@ pub struct AuthResponse<T> {
     /// The token is used as the Authorization header for all requests.
     /// It is also saved and cloned in the [AuthStore].
     pub token: String,
+    /// Additional data about the OAuth2 provider's account.
+    /// Only present when authenticating with OAuth2.
+    #[serde(default)]
+    pub meta: Option<OAuth2Meta>,
+}
+
+/// The data of the user's account on the OAuth2 provider,
+/// as returned by an OAuth2 authentication.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2Meta {
+    /// The ID of the user on the provider.
+    #[serde(default)]
+    pub id: String,
+    /// The name of the user on the provider.
+    #[serde(default)]
+    pub name: String,
+    /// The username of the user on the provider.
+    #[serde(default)]
+    pub username: String,
+    /// The email address of the user on the provider.
+    #[serde(default)]
+    pub email: String,
+    /// Whether the record was created by this authentication.
+    #[serde(default)]
+    pub is_new: bool,
+    /// The URL of the user's avatar on the provider.
+    #[serde(default, rename = "avatarURL")]
+    pub avatar_url: String,
+    /// The access token given by the provider.
+    #[serde(default)]
+    pub access_token: String,
+    /// The refresh token given by the provider.
+    #[serde(default)]
+    pub refresh_token: String,
+    /// The expiration date of the access token.
+    #[serde(default)]
+    pub expiry: String,
+    /// The raw data of the user, as returned by the provider.
+    #[serde(default)]
+    pub raw_user: serde_json::Value,
+}
+
+/// The authentication methods allowed by an auth collection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthMethodsList {
+    /// The authentication with an identity and a password.
+    pub password: PasswordAuthConfig,
+    /// The authentication with OAuth2 providers.
+    pub oauth2: OAuth2AuthConfig,
+    /// The multi-factor authentication.
+    pub mfa: MfaAuthConfig,
+    /// The authentication with a one-time password.
+    pub otp: OtpAuthConfig,
+}
+
+/// Describes whether the authentication with a password is allowed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordAuthConfig {
+    /// Whether this method is enabled.
+    pub enabled: bool,
+    /// The fields that can be used as the identity (usually `email`).
+    #[serde(default)]
+    pub identity_fields: Vec<String>,
+}
+
+/// Describes the OAuth2 providers that can be used.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OAuth2AuthConfig {
+    /// Whether this method is enabled.
+    pub enabled: bool,
+    /// The configured providers.
+    #[serde(default)]
+    pub providers: Vec<AuthProviderInfo>,
+}
+
+/// The information needed to authenticate with an OAuth2 provider.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthProviderInfo {
+    /// The name of the provider (`github`, `google`, etc.).
+    pub name: String,
+    /// The name of the provider meant to be displayed.
+    pub display_name: String,
+    /// A random string that must be compared with the `state` query parameter
+    /// of the redirect URL, to prevent CSRF attacks.
+    pub state: String,
+    /// The URL of the provider's login page.
+    /// The redirect URL must be appended to it.
+    #[serde(rename = "authURL")]
+    pub auth_url: String,
+    /// The PKCE code verifier, to be sent back when exchanging the code.
+    pub code_verifier: String,
+    /// The PKCE code challenge.
+    pub code_challenge: String,
+    /// The method used to create the PKCE code challenge.
+    pub code_challenge_method: String,
+}
+
+/// Describes whether the multi-factor authentication is required.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MfaAuthConfig {
+    /// Whether this method is enabled.
+    pub enabled: bool,
+    /// The duration (in seconds) during which the second factor can be given.
+    #[serde(default)]
+    pub duration: u64,
+}
+
+/// Describes whether the authentication with a one-time password is allowed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OtpAuthConfig {
+    /// Whether this method is enabled.
+    pub enabled: bool,
+    /// The duration (in seconds) during which the password is valid.
+    #[serde(default)]
+    pub duration: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -85,6 +216,72 @@ pub(crate) struct AuthRequestPayload {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MfaChallenge {
+    pub mfa_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ImpersonatePayload {
+    pub duration: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct EmailPayload {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TokenPayload {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfirmPasswordResetPayload {
+    pub token: String,
+    pub password: String,
+    pub password_confirm: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EmailChangePayload {
+    pub new_email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ConfirmEmailChangePayload {
+    pub token: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OtpResponse {
+    pub otp_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OtpAuthPayload {
+    pub otp_id: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OAuth2CodePayload {
+    pub provider: String,
+    pub code: String,
+    pub code_verifier: String,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_data: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct JwtPayload {
     #[serde(rename = "type")]
@@ -92,8 +289,10 @@ pub(crate) struct JwtPayload {
     #[serde(rename = "collectionId")]
     collection_id: String,
     exp: u64, // expiration in seconds
-    // refreshable: bool,
-    // id: String,
+    #[serde(default)]
+    refreshable: bool,
+    #[serde(default)]
+    id: String,
 }
 
 impl<T> AuthStore<T>
@@ -115,6 +314,19 @@ where T: DeserializeOwned + Clone {
         self.record_id = Some(record_id);
     }
 
+    /// Fills the store with the given token and raw record.
+    /// Fails if the record cannot be deserialized.
+    pub(crate) fn load_data(&mut self, data: &AuthData) -> Result<(), ApiError> {
+        let response_record = serde_json::from_value::<DefaultAuthResponseRecord>(data.record.clone())?;
+        let record = serde_json::from_value::<T>(data.record.clone())?;
+        self.set_token(data.token.clone());
+        self.set_collection(response_record.collection_name, response_record.collection_id);
+        self.set_record_id(response_record.id);
+        self.set_record(record);
+        self.raw_record = Some(data.record.clone());
+        Ok(())
+    }
+
     pub(crate) fn is_some(&self) -> bool {
         self.token.is_some() && self.record.is_some() && self.collection_id.is_some() && self.collection_name.is_some()
     }
@@ -135,6 +347,47 @@ where T: DeserializeOwned + Clone {
         false
     }
 
+    /// Checks if the given token (of any type) was issued for the authenticated record.
+    pub(crate) fn is_token_of_current_record(&self, token: &str) -> bool {
+        if !self.is_some() { return false; }
+        match get_token_payload(token) {
+            Ok(payload) => self.record_id.as_ref() == Some(&payload.id) && self.collection_id.as_ref() == Some(&payload.collection_id),
+            Err(_) => false,
+        }
+    }
+
+    /// Exports the store as the value of a `Set-Cookie` header,
+    /// compatible with the `exportToCookie()` method of the JavaScript SDK.
+    ///
+    /// If the cookie exceeds 4096 bytes, the record is stripped down to
+    /// its `id`, `email`, `collectionId`, `collectionName` and `verified` properties.
+    pub fn export_to_cookie(&self, options: Option<CookieOptions>) -> String {
+        let token_exp = self.token.as_ref().and_then(|token| get_token_payload(token).ok()).map(|payload| payload.exp);
+        let data = serde_json::json!({
+            "token": self.token.clone().unwrap_or_default(),
+            "record": self.raw_record.clone(),
+        });
+        cookie_serialize(token_exp, data, options.unwrap_or_default())
+    }
+
+    /// Loads the store from the `pb_auth` cookie of a `Cookie` or `Set-Cookie` header,
+    /// compatible with the `loadFromCookie()` method of the JavaScript SDK.
+    /// The store is cleared if the header doesn't contain such cookie.
+    ///
+    /// Fails if the cookie isn't valid JSON, or if its record cannot be deserialized,
+    /// which might happen if it was stripped down because of its size.
+    /// Like the JavaScript SDK, the store is cleared in that case.
+    pub fn load_from_cookie(&mut self, header: &str) -> Result<(), ApiError> {
+        let loaded = cookie_auth_data(header).and_then(|data| match data {
+            Some(data) => self.load_data(&data).map(|_| true),
+            None => Ok(false),
+        });
+        if !matches!(loaded, Ok(true)) {
+            self.clear();
+        }
+        loaded.map(|_| ())
+    }
+
     /// Clears all user-related information.
     /// Use this if you want the user to log out.
     pub fn clear(&mut self) {
@@ -143,10 +396,11 @@ where T: DeserializeOwned + Clone {
         self.collection_id = None;
         self.collection_name = None;
         self.record_id = None;
+        self.raw_record = None;
     }
 }
 
-pub(crate) fn get_token_payload(token: &String) -> Result<JwtPayload, ApiError> {
+pub(crate) fn get_token_payload(token: &str) -> Result<JwtPayload, ApiError> {
     let payload = token.split('.').nth(1).ok_or("Invalid token");
     if let Ok(payload) = payload {
         let decoded = URL_SAFE_NO_PAD.decode(payload);
@@ -163,16 +417,27 @@ pub(crate) fn get_token_payload(token: &String) -> Result<JwtPayload, ApiError>
     Err(ApiError::Jwt())
 }
 
-pub(crate) fn is_token_expired(token: &String) -> bool {
+pub(crate) fn is_token_expired(token: &str) -> bool {
+    is_token_expiring(token, Duration::ZERO)
+}
+
+/// Checks if the token expires within the given window,
+/// in which case it should be refreshed.
+pub(crate) fn is_token_expiring(token: &str, window: Duration) -> bool {
     let payload = get_token_payload(token);
     if let Ok(payload) = payload {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
             .as_secs();
-        if payload.exp > timestamp {
+        if payload.exp > timestamp.saturating_add(window.as_secs()) {
             return false;
         }
     }
     true
-}
\ No newline at end of file
+}
+
+/// Checks if the token can be renewed with the `auth-refresh` route.
+pub(crate) fn is_token_refreshable(token: &str) -> bool {
+    get_token_payload(token).is_ok_and(|payload| payload.refreshable)
+}
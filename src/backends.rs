@@ -0,0 +1,134 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::error::ApiError;
+
+/// The data of the [AuthStore](crate::AuthStore) that is persisted by an [AuthStoreBackend].
+/// It has the same shape as the data saved by the JavaScript SDK.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthData {
+    /// The JWT token.
+    pub token: String,
+    /// The raw JSON record of the authenticated user.
+    pub record: serde_json::Value,
+}
+
+/// Persists the authentication of a [PocketBase](crate::PocketBase) instance.
+///
+/// The [AuthStore](crate::AuthStore) itself always lives in memory,
+/// the backend is notified of every change so that it can save it somewhere else,
+/// and is read once, when the [PocketBase](crate::PocketBase) instance is created.
+///
+/// Implement this trait to keep the authentication in a keyring, an encrypted file, a database, etc.
+///
+/// The methods are synchronous. They are called right after an authentication, a refresh, or a logout,
+/// on a blocking thread when it happens in an async method of the SDK,
+/// and on the current thread from [PocketBase::clear_auth_store](crate::PocketBase::clear_auth_store)
+/// and [PocketBase::load_auth_from_cookie](crate::PocketBase::load_auth_from_cookie).
+pub trait AuthStoreBackend: Send + Sync {
+    /// Saves the data of a new authentication, or of an updated record.
+    fn save(&self, data: &AuthData) -> Result<(), ApiError>;
+
+    /// Loads the previously saved data, if there is any.
+    /// Only an [ApiError::Io] prevents the [PocketBase](crate::PocketBase) instance from being created,
+    /// other errors are treated as if there was no data.
+    fn load(&self) -> Result<Option<AuthData>, ApiError>;
+
+    /// Removes the saved data, because the user logged out.
+    fn clear(&self) -> Result<(), ApiError>;
+}
+
+/// The default backend, which keeps the data in memory only.
+/// The authentication is lost when the process ends.
+#[derive(Debug, Default)]
+pub struct InMemoryBackend {
+    data: Mutex<Option<AuthData>>,
+}
+
+impl AuthStoreBackend for InMemoryBackend {
+    fn save(&self, data: &AuthData) -> Result<(), ApiError> {
+        *self.data.lock().unwrap() = Some(data.clone());
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<AuthData>, ApiError> {
+        Ok(self.data.lock().unwrap().clone())
+    }
+
+    fn clear(&self) -> Result<(), ApiError> {
+        *self.data.lock().unwrap() = None;
+        Ok(())
+    }
+}
+
+/// A backend that saves the data in a JSON file,
+/// so that the user stays authenticated across restarts.
+///
+/// The file contains the token in plain text,
+/// make sure it is stored somewhere only the user can read.
+/// On Unix, the file is only readable by its owner.
+///
+/// The file is read and written with blocking I/O, see [AuthStoreBackend] for the thread it runs on.
+#[derive(Debug)]
+pub struct JsonFileBackend {
+    path: PathBuf,
+}
+
+impl JsonFileBackend {
+    /// Creates a backend saving the data at the given path.
+    /// The file doesn't need to exist, it's created on the first save.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonFileBackend {
+            path: path.into(),
+        }
+    }
+
+    /// The path of the JSON file.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl AuthStoreBackend for JsonFileBackend {
+    fn save(&self, data: &AuthData) -> Result<(), ApiError> {
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+        // The data is written to a temporary file that replaces the previous one,
+        // so that the file is never left half-written.
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+        match fs::remove_file(&temporary_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temporary_path)?;
+        file.write_all(serde_json::to_string(data)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, &self.path)?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<AuthData>, ApiError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn clear(&self) -> Result<(), ApiError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,5 +1,9 @@
+use reqwest::StatusCode;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use urlencoding::encode;
+use crate::error::ApiError;
+use crate::expand::Expandable;
 
 /// Describes an expected error returned by an API route of PocketBase.
 #[derive(Debug, Deserialize)]
@@ -10,6 +14,68 @@ pub struct ResponseError {
     pub status: u16,
 }
 
+/// Converts the status of an error body, which may not be valid
+/// if it was returned by a proxy or a hook, into a [StatusCode].
+pub(crate) fn status_code(status: u16) -> StatusCode {
+    StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Deserializes the body of a response, or the error that the server returned instead.
+pub(crate) fn handle_response_body<E: DeserializeOwned>(body: &str) -> Result<E, ApiError> {
+    match serde_json::from_str::<E>(body) {
+        Ok(response) => Ok(response),
+        Err(err) => match serde_json::from_str::<ResponseError>(body) {
+            Ok(error) => Err(ApiError::Http(status_code(error.status), error.message)),
+            Err(_) => Err(ApiError::Json(err)),
+        },
+    }
+}
+
+/// The properties that PocketBase includes in every record,
+/// and that are needed to locate it (to build the URL of its files for example).
+///
+/// It's implemented by the structs using `#[base_system_fields]`,
+/// and by raw JSON records.
+pub trait BaseSystemFields {
+    /// The ID of the record.
+    fn id(&self) -> &str;
+    /// The ID of the collection of the record.
+    fn collection_id(&self) -> &str;
+    /// The name of the collection of the record.
+    fn collection_name(&self) -> &str;
+}
+
+impl BaseSystemFields for serde_json::Value {
+    fn id(&self) -> &str {
+        self.get("id").and_then(|x| x.as_str()).unwrap_or_default()
+    }
+
+    fn collection_id(&self) -> &str {
+        self.get("collectionId").and_then(|x| x.as_str()).unwrap_or_default()
+    }
+
+    fn collection_name(&self) -> &str {
+        self.get("collectionName").and_then(|x| x.as_str()).unwrap_or_default()
+    }
+}
+
+/// Handles the body of a response that is expected to be empty,
+/// which is the case of the routes answering with a `204 No Content`.
+pub(crate) fn handle_empty_response_body(body: &str) -> Result<(), ApiError> {
+    if body.is_empty() {
+        return Ok(());
+    }
+    match serde_json::from_str::<ResponseError>(body) {
+        Ok(error) => Err(ApiError::Http(status_code(error.status), error.message)),
+        Err(_) => Ok(()),
+    }
+}
+
+fn expand_option<R: Expandable>() -> Option<String> {
+    let query = R::expand_query();
+    if query.is_empty() { None } else { Some(query) }
+}
+
 /// Options to view a collection's record.
 #[derive(Debug, Default)]
 pub struct ViewOptions {
@@ -22,6 +88,32 @@ pub struct ViewOptions {
     pub sort: Option<String>,
 }
 
+/// Options for the authentication routes.
+#[derive(Debug, Default, Clone)]
+pub struct AuthOptions {
+    /// The ID given by [ApiError::MfaRequired] after the first authentication.
+    /// Giving it to a second authentication, made with another method, completes the MFA.
+    pub mfa_id: Option<String>,
+}
+
+impl AuthOptions {
+    /// Creates the options to complete a multi-factor authentication.
+    pub fn mfa(mfa_id: impl Into<String>) -> Self {
+        AuthOptions {
+            mfa_id: Some(mfa_id.into()),
+        }
+    }
+
+    pub(crate) fn to_url_query(&self) -> String {
+        let mut url = "?".to_string();
+        if let Some(mfa_id) = &self.mfa_id { url.push_str(&format!("mfaId={}&", encode(mfa_id).into_owned())); }
+        if url.len() == 1 {
+            return String::new();
+        }
+        url.strip_suffix("&").unwrap().to_string()
+    }
+}
+
 /// The query parameters for the API route `/api/collections/NAME/records`.
 /// This route would return paginated results by default.
 #[derive(Debug, Default, Clone)]
@@ -47,6 +139,15 @@ pub struct ListOptions {
 }
 
 impl ListOptions {
+    /// Creates options expanding all the relations declared by the record type,
+    /// see [Expandable].
+    pub fn expanded<R: Expandable>() -> Self {
+        ListOptions {
+            expand: expand_option::<R>(),
+            ..ListOptions::default()
+        }
+    }
+
     /// Creates a simple instance that will only care about
     /// the page number and the amount of items per page.
     pub fn paginated(page: u64, per_page: u64) -> Self {
@@ -82,7 +183,8 @@ impl ListOptions {
         }
     }
 
-    pub(crate) fn to_url_query(&self) -> String {
+    /// The query string of the options, followed by additional query parameters.
+    pub(crate) fn to_url_query_with(&self, query: &[(String, String)]) -> String {
         let mut url = "?".to_string();
         if let Some(page) = self.page { url.push_str(&format!("page={}&", page)); }
         if let Some(per_page) = self.per_page { url.push_str(&format!("perPage={}&", per_page)); }
@@ -91,6 +193,7 @@ impl ListOptions {
         if let Some(fields) = &self.fields { url.push_str(&format!("fields={}&", encode(fields).into_owned())); }
         if let Some(expand) = &self.expand { url.push_str(&format!("expand={}&", encode(expand).into_owned())); }
         if let Some(sort) = &self.sort { url.push_str(&format!("sort={}&", encode(sort).into_owned())); }
+        for (key, value) in query { url.push_str(&format!("{}={}&", encode(key).into_owned(), encode(value).into_owned())); }
         if url.len() == 1 {
             return String::new();
         }
@@ -98,9 +201,61 @@ impl ListOptions {
     }
 }
 
+/// The options of `get_full_list_with()`, applied to every page request.
+#[derive(Debug, Default, Clone)]
+pub struct FullListOptions {
+    /// The number of records fetched per request, 1000 by default.
+    pub batch: Option<u64>,
+    /// Filter the returned records.
+    pub filter: Option<String>,
+    /// Comma separated string of the fields to return
+    /// in the JSON response (by default returns all fields).
+    pub fields: Option<String>,
+    /// Auto expand record relations.
+    pub expand: Option<String>,
+    /// Specify the records order attribute.
+    pub sort: Option<String>,
+    /// Additional query parameters, for example the ones read by custom hooks.
+    pub query: Vec<(String, String)>,
+    /// The maximum number of pages requested at the same time.
+    /// By default, the pages are requested one after the other.
+    ///
+    /// If it's greater than 1, the first page is requested alone to know the total number of pages,
+    /// then the remaining ones are requested concurrently and reassembled in order.
+    /// Records created or deleted in the meantime may then be missed or duplicated,
+    /// so a stable `sort` is recommended.
+    pub concurrency: Option<usize>,
+}
+
+impl FullListOptions {
+    /// Splits the options into the ones of each page request and the additional query parameters.
+    pub(crate) fn into_parts(self) -> (ListOptions, Vec<(String, String)>) {
+        let options = ListOptions {
+            page: None,
+            per_page: self.batch,
+            skip_total: Some(true),
+            filter: self.filter,
+            fields: self.fields,
+            expand: self.expand,
+            sort: self.sort,
+        };
+        (options, self.query)
+    }
+}
+
 impl ViewOptions {
+    /// Creates options expanding all the relations declared by the record type,
+    /// see [Expandable].
+    pub fn expanded<R: Expandable>() -> Self {
+        ViewOptions {
+            expand: expand_option::<R>(),
+            ..ViewOptions::default()
+        }
+    }
+
     pub(crate) fn to_url_query(&self) -> String {
         let mut url = "?".to_string();
+        if let Some(fields) = &self.fields { url.push_str(&format!("fields={}&", encode(fields).into_owned())); }
         if let Some(expand) = &self.expand { url.push_str(&format!("expand={}&", encode(expand).into_owned())); }
         if let Some(sort) = &self.sort { url.push_str(&format!("sort={}&", encode(sort).into_owned())); }
         if url.len() == 1 {
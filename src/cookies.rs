@@ -1,78 +1,123 @@
-use std::borrow::Cow;
-use std::collections::HashMap;
-use serde::{Deserialize, Deserializer};
-use urlencoding::decode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
+use urlencoding::{decode, encode};
+use crate::backends::AuthData;
+use crate::error::ApiError;
 
-#[derive(Debug, Deserialize)]
-pub struct Cookie {
-    pub name: Option<String>,
-    pub value: Option<String>,
-    #[serde(rename = "Path")]
-    pub path: Option<String>,
-    #[serde(rename = "Expires")]
-    pub expires: Option<String>,
-    #[serde(rename = "SameSite")]
-    pub same_site: Option<String>,
-    #[serde(rename = "HttpOnly")]
-    #[serde(deserialize_with = "string_to_bool")]
+/// The name of the cookie used by the JavaScript SDK.
+pub(crate) const COOKIE_KEY: &str = "pb_auth";
+
+/// Browsers reject the cookies that are bigger than this.
+const MAX_COOKIE_SIZE: usize = 4096;
+
+/// The properties of the record that are kept when the cookie is too big.
+const MINIMAL_RECORD_PROPS: [&str; 5] = ["id", "email", "collectionId", "collectionName", "verified"];
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    /// `SameSite=Strict`
+    Strict,
+    /// `SameSite=Lax`
+    Lax,
+    /// `SameSite=None`, which requires the cookie to be `Secure`.
+    None,
+}
+
+/// The attributes of the cookie created by [AuthStore::export_to_cookie](crate::AuthStore::export_to_cookie).
+/// The default values are the same as the ones of the JavaScript SDK.
+#[derive(Debug, Clone)]
+pub struct CookieOptions {
+    /// The `HttpOnly` attribute, `true` by default.
     pub http_only: bool,
-    #[serde(rename = "Secure")]
-    #[serde(deserialize_with = "string_to_bool")]
+    /// The `Secure` attribute, `true` by default.
     pub secure: bool,
+    /// The `SameSite` attribute, `Strict` by default.
+    pub same_site: Option<SameSite>,
+    /// The `Path` attribute, `/` by default.
+    pub path: Option<String>,
+    /// The `Domain` attribute, none by default.
+    pub domain: Option<String>,
+    /// The `Max-Age` attribute, none by default.
+    pub max_age: Option<Duration>,
+    /// The `Expires` attribute. By default, it's the expiration date of the token.
+    pub expires: Option<SystemTime>,
 }
 
-impl Default for Cookie {
+impl Default for CookieOptions {
     fn default() -> Self {
-        Self {
-            name: None,
-            value: None,
+        CookieOptions {
+            http_only: true,
+            secure: true,
+            same_site: Some(SameSite::Strict),
+            path: Some("/".to_string()),
+            domain: None,
+            max_age: None,
             expires: None,
-            path: None,
-            same_site: None,
-            http_only: false,
-            secure: false,
         }
     }
 }
 
-/// This is used in macros only, in the [Cookie] struct.
-#[allow(unused)]
-fn string_to_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    match s.as_str() {
-        "true" => Ok(true),
-        "false" => Ok(false),
-        _ => Err(serde::de::Error::custom("expected \"true\" or \"false\"")),
+/// Extracts the decoded value of the `pb_auth` cookie from either a `Cookie` or a `Set-Cookie` header.
+pub(crate) fn cookie_value(header: &str) -> Option<String> {
+    header.split(';').find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        if key.trim() != COOKIE_KEY {
+            return None;
+        }
+        let value = value.trim();
+        let value = value.strip_prefix('"').and_then(|x| x.strip_suffix('"')).unwrap_or(value);
+        Some(decode(value).map(|x| x.into_owned()).unwrap_or_else(|_| value.to_string()))
+    })
+}
+
+/// The JSON value of the cookie.
+/// Old versions of the JavaScript SDK used `model` instead of `record`.
+#[derive(Debug, Deserialize)]
+struct CookieData {
+    #[serde(default)]
+    token: String,
+    record: Option<serde_json::Value>,
+    model: Option<serde_json::Value>,
+}
+
+/// Extracts the authentication data from the `pb_auth` cookie of the header.
+/// Returns [Option::None] if there is no such cookie, or if it's empty.
+pub(crate) fn cookie_auth_data(header: &str) -> Result<Option<AuthData>, ApiError> {
+    let Some(value) = cookie_value(header).filter(|x| !x.is_empty()) else { return Ok(None) };
+    let data = serde_json::from_str::<CookieData>(&value)?;
+    match data.record.or(data.model) {
+        Some(record) if !data.token.is_empty() && record.is_object() => Ok(Some(AuthData { token: data.token, record })),
+        _ => Ok(None),
+    }
+}
+
+/// Builds the `Set-Cookie` value holding the given JSON data.
+/// `token_exp` is the expiration of the token, in seconds, used when no expiration date is given.
+pub(crate) fn cookie_serialize(token_exp: Option<u64>, mut data: serde_json::Value, options: CookieOptions) -> String {
+    let expires = options.expires.unwrap_or_else(|| UNIX_EPOCH + Duration::from_secs(token_exp.unwrap_or(0)));
+    let mut result = serialize_with_attributes(&data, &options, expires);
+    if result.len() > MAX_COOKIE_SIZE
+        && let Some(record) = data.get_mut("record").and_then(|x| x.as_object_mut()) {
+        record.retain(|key, _| MINIMAL_RECORD_PROPS.contains(&key.as_str()));
+        result = serialize_with_attributes(&data, &options, expires);
     }
+    result
 }
 
-pub(crate) fn cookie_parse(str: &String) -> Result<Cookie, ()> {
-    let mut map = HashMap::<String, String>::from_iter(str.split(';').map(|x| {
-        let has_equal = x.contains("=");
-        return if has_equal {
-            let eq_idx = x.find("=").unwrap();
-            let sliced_key = (&x[0..eq_idx]).trim().to_string();
-            let key = if sliced_key == "pb_auth" { "value".to_string() } else { sliced_key };
-            let value = (&x[eq_idx + 1..]).trim().to_string();
-            if let Some(first_char) = x.chars().next() {
-                if let Some(last_char) = x.chars().last() {
-                    if (first_char == last_char) && first_char == '"' {
-                        let unquoted_value = (&x[1..value.chars().count() - 1]).to_string();
-                        let decoded_value = decode(&unquoted_value).unwrap_or(Cow::Borrowed(&unquoted_value));
-                        return (key, decoded_value.to_string());
-                    }
-                }
-            }
-            (key, decode(&value).unwrap_or(Cow::Borrowed(&value)).to_string())
-        } else {
-            (x.trim().to_string(), String::new())
-        }
-    }));
-    if map.contains_key("value") && !map["value"].is_empty() { map.insert("name".into(), "pb_auth".into()); }
-    if map.contains_key("HttpOnly") && map["HttpOnly"].is_empty() { map.insert("HttpOnly".into(), "false".into()); }
-    if map.contains_key("Secure") && map["Secure"].is_empty() { map.insert("Secure".into(), "false".into()); }
-    Ok(serde_json::from_value(serde_json::to_value(map).unwrap()).unwrap())
-}
\ No newline at end of file
+fn serialize_with_attributes(data: &serde_json::Value, options: &CookieOptions, expires: SystemTime) -> String {
+    let mut result = format!("{}={}", COOKIE_KEY, encode(&data.to_string()));
+    if let Some(max_age) = options.max_age { result.push_str(&format!("; Max-Age={}", max_age.as_secs())); }
+    if let Some(domain) = &options.domain { result.push_str(&format!("; Domain={}", domain)); }
+    if let Some(path) = &options.path { result.push_str(&format!("; Path={}", path)); }
+    result.push_str(&format!("; Expires={}", httpdate::fmt_http_date(expires)));
+    if options.http_only { result.push_str("; HttpOnly"); }
+    if options.secure { result.push_str("; Secure"); }
+    match options.same_site {
+        Some(SameSite::Strict) => result.push_str("; SameSite=Strict"),
+        Some(SameSite::Lax) => result.push_str("; SameSite=Lax"),
+        Some(SameSite::None) => result.push_str("; SameSite=None"),
+        None => {}
+    }
+    result
+}
@@ -0,0 +1,231 @@
+//! The dates of PocketBase, such as the `created` and `updated` fields,
+//! which are formatted as `2026-01-28 10:02:31.589Z`.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// The error returned when a string isn't a valid PocketBase date.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid PocketBase date: {0:?}")]
+pub struct PbDateTimeError(String);
+
+/// A UTC date with a precision of one millisecond,
+/// formatted the way PocketBase does: `2026-01-28 10:02:31.589Z`.
+///
+/// The `date` fields that aren't required are empty strings when they aren't set.
+/// Such fields must be an `Option<PbDateTime>` using [optional]:
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// #[derive(serde::Deserialize)]
+/// struct Event {
+///     #[serde(default, with = "pbrsdk::datetime::optional")]
+///     starts_at: Option<PbDateTime>,
+/// }
+///
+/// let event: Event = serde_json::from_str(r#"{"starts_at":""}"#).unwrap();
+/// assert!(event.starts_at.is_none());
+/// let date: PbDateTime = "2026-01-28 10:02:31.589Z".parse().unwrap();
+/// assert_eq!(date.to_string(), "2026-01-28 10:02:31.589Z");
+/// ```
+///
+/// The default date is the Unix epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PbDateTime {
+    millis: i64,
+}
+
+impl PbDateTime {
+    /// The current date.
+    pub fn now() -> Self {
+        PbDateTime::from(SystemTime::now())
+    }
+
+    /// Creates a date from the number of milliseconds since the Unix epoch.
+    pub fn from_unix_millis(millis: i64) -> Self {
+        PbDateTime { millis }
+    }
+
+    /// The number of milliseconds since the Unix epoch.
+    pub fn unix_millis(&self) -> i64 {
+        self.millis
+    }
+}
+
+/// The number of days since 1970-01-01 of a civil date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// The civil date of a number of days since 1970-01-01 (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for PbDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.millis.div_euclid(MILLIS_PER_DAY));
+        let millis_of_day = self.millis.rem_euclid(MILLIS_PER_DAY);
+        let secs_of_day = millis_of_day / 1000;
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}Z",
+            year, month, day, secs_of_day / 3600, secs_of_day % 3600 / 60, secs_of_day % 60, millis_of_day % 1000,
+        )
+    }
+}
+
+impl FromStr for PbDateTime {
+    type Err = PbDateTimeError;
+
+    /// Parses `2026-01-28 10:02:31.589Z`.
+    /// The RFC 3339 `T` separator is accepted too, and the milliseconds are optional.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || PbDateTimeError(value.to_string());
+        let number = |range: std::ops::Range<usize>| -> Result<i64, PbDateTimeError> {
+            let digits = value.get(range).ok_or_else(error)?;
+            if !digits.bytes().all(|x| x.is_ascii_digit()) {
+                return Err(error());
+            }
+            digits.parse().map_err(|_| error())
+        };
+        let bytes = value.as_bytes();
+        if bytes.len() < 19 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b' ' | b'T') || bytes[13] != b':' || bytes[16] != b':' {
+            return Err(error());
+        }
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+            return Err(error());
+        }
+        let rest = value[19..].strip_suffix('Z').unwrap_or(&value[19..]);
+        let millis = match rest.strip_prefix('.') {
+            Some(fraction) if !fraction.is_empty() && fraction.bytes().all(|x| x.is_ascii_digit()) => {
+                format!("{:0<3}", &fraction[..fraction.len().min(3)]).parse::<i64>().map_err(|_| error())?
+            }
+            None if rest.is_empty() => 0,
+            _ => return Err(error()),
+        };
+        let days = days_from_civil(year, month, day);
+        Ok(PbDateTime::from_unix_millis(days * MILLIS_PER_DAY + ((hour * 60 + minute) * 60 + second) * 1000 + millis))
+    }
+}
+
+impl From<SystemTime> for PbDateTime {
+    fn from(time: SystemTime) -> Self {
+        let millis = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as i64,
+            // Rounded down, like the dates after the epoch.
+            Err(err) => {
+                let duration = err.duration();
+                let millis = -(duration.as_millis() as i64);
+                if duration.subsec_nanos() % 1_000_000 != 0 { millis - 1 } else { millis }
+            }
+        };
+        PbDateTime::from_unix_millis(millis)
+    }
+}
+
+impl From<PbDateTime> for SystemTime {
+    fn from(date: PbDateTime) -> Self {
+        let duration = Duration::from_millis(date.millis.unsigned_abs());
+        if date.millis >= 0 { UNIX_EPOCH + duration } else { UNIX_EPOCH - duration }
+    }
+}
+
+impl Serialize for PbDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PbDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes an `Option<PbDateTime>`, which is an empty string when it's [Option::None],
+/// like the `date` fields that aren't set.
+///
+/// Use it with `#[serde(default, with = "pbrsdk::datetime::optional")]`.
+pub mod optional {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use super::PbDateTime;
+
+    /// Serializes [Option::None] as an empty string.
+    pub fn serialize<S: Serializer>(value: &Option<PbDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(date) => serializer.collect_str(date),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    /// Deserializes an empty string, or null, as [Option::None].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<PbDateTime>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(value) if !value.is_empty() => value.parse().map(Some).map_err(serde::de::Error::custom),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for PbDateTime {
+    fn from(date: chrono::DateTime<chrono::Utc>) -> Self {
+        PbDateTime::from_unix_millis(date.timestamp_millis())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<PbDateTime> for chrono::DateTime<chrono::Utc> {
+    type Error = PbDateTimeError;
+
+    fn try_from(date: PbDateTime) -> Result<Self, Self::Error> {
+        chrono::DateTime::from_timestamp_millis(date.millis).ok_or_else(|| PbDateTimeError(date.to_string()))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for PbDateTime {
+    fn from(date: time::OffsetDateTime) -> Self {
+        PbDateTime::from_unix_millis(date.unix_timestamp_nanos().div_euclid(1_000_000) as i64)
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<PbDateTime> for time::OffsetDateTime {
+    type Error = PbDateTimeError;
+
+    fn try_from(date: PbDateTime) -> Result<Self, Self::Error> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(date.millis as i128 * 1_000_000).map_err(|_| PbDateTimeError(date.to_string()))
+    }
+}
@@ -11,8 +11,42 @@ pub enum ApiError {
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
+    /// The data couldn't be serialized or deserialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// An I/O error, for example while reading or writing a file.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     /// Un unexpected error that was triggered by an invalid JWT token.
     /// Will happen only if the JWT is corrupted, not if it expired.
     #[error("Invalid token")]
-    Jwt()
+    Jwt(),
+
+    /// The credentials are valid but the collection requires a second authentication method.
+    /// Authenticate again with another method, giving this `mfa_id` in the [AuthOptions](crate::AuthOptions).
+    #[error("Multi-factor authentication required (mfaId: {mfa_id})")]
+    MfaRequired {
+        /// The ID of the MFA session, to give to the second authentication.
+        mfa_id: String,
+    },
+
+    /// A batch failed, so none of its requests were applied.
+    #[error("Batch error {status}: {message}")]
+    Batch {
+        /// The HTTP status of the batch.
+        status: reqwest::StatusCode,
+        /// The message that the server returned.
+        message: String,
+        /// The details of the failure, as returned by the server.
+        /// The errors of the failed requests are under `requests`, by index,
+        /// such as `{"requests":{"1":{"code":"batch_request_failed","response":{...}}}}`.
+        data: serde_json::Value,
+    },
+
+    /// The realtime connection couldn't be established,
+    /// or was closed by the server before the handshake.
+    #[error("Realtime connection error: {0}")]
+    Realtime(String),
 }
@@ -0,0 +1,127 @@
+use std::ops::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+
+/// The maximum depth of the expanded relations supported by PocketBase.
+pub const MAX_EXPAND_DEPTH: usize = 6;
+
+/// The types whose relations can be expanded,
+/// implemented by the structs using `#[base_system_fields]`.
+///
+/// The relations are listed with the `#[expand(...)]` attribute,
+/// see [base_system_fields](crate::base_system_fields).
+pub trait Expandable {
+    /// The paths of the relations to expand, such as `author` and `author.company`.
+    fn expand_paths() -> Vec<String> {
+        Self::expand_paths_within(&mut Vec::new())
+    }
+
+    /// The paths of the relations to expand for records nested in the given records,
+    /// listed by their type names from the fetched record.
+    /// A relation to a record that is already in this list isn't expanded any further,
+    /// so a cyclic relation is expanded once, and the relations deeper than [MAX_EXPAND_DEPTH] are ignored.
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String>;
+
+    /// The value of the `expand` query parameter, such as `author,author.company`.
+    fn expand_query() -> String {
+        Self::expand_paths().join(",")
+    }
+}
+
+impl Expandable for serde_json::Value {
+    fn expand_paths_within(_ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl<T: Expandable> Expandable for Vec<T> {
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        T::expand_paths_within(ancestors)
+    }
+}
+
+impl<T: Expandable> Expandable for Box<T> {
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        T::expand_paths_within(ancestors)
+    }
+}
+
+impl<T: Expandable> Expandable for Option<T> {
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        T::expand_paths_within(ancestors)
+    }
+}
+
+/// An expanded relation: a single record (`Relation<User>`), or several (`Relation<Vec<Tag>>`).
+/// A record related to its own collection must be boxed (`Relation<Box<Category>>`).
+///
+/// It's empty when the relation wasn't expanded, when the field is empty,
+/// or when the related records can't be viewed by the current user.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Relation<T>(Option<T>);
+
+impl<T> Relation<T> {
+    /// Wraps expanded records.
+    pub fn new(value: T) -> Self {
+        Relation(Some(value))
+    }
+
+    /// The expanded records, if any.
+    pub fn get(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+
+    /// Whether the relation was expanded.
+    pub fn is_expanded(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Returns the expanded records, if any.
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> Default for Relation<T> {
+    fn default() -> Self {
+        Relation(None)
+    }
+}
+
+impl<T> From<Option<T>> for Relation<T> {
+    fn from(value: Option<T>) -> Self {
+        Relation(value)
+    }
+}
+
+impl<T: Expandable> Expandable for Relation<T> {
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        T::expand_paths_within(ancestors)
+    }
+}
+
+/// The `expand` property of a record, holding its expanded relations.
+/// It dereferences to the struct listing the relations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Expand<T>(pub T);
+
+impl<T> Deref for Expand<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Expand<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Expandable> Expandable for Expand<T> {
+    fn expand_paths_within(ancestors: &mut Vec<&'static str>) -> Vec<String> {
+        T::expand_paths_within(ancestors)
+    }
+}
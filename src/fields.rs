@@ -0,0 +1,419 @@
+use serde::{Deserialize, Serialize};
+
+/// The properties shared by all the fields of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldBase {
+    /// The ID of the field. Leave it empty to let the server generate it.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// The name of the field, which is the name of the column.
+    pub name: String,
+    /// Whether the field is a system field, which cannot be deleted or renamed.
+    pub system: bool,
+    /// Whether the field is hidden from the API responses.
+    pub hidden: bool,
+    /// Whether the field is used to represent the record in the dashboard.
+    pub presentable: bool,
+}
+
+impl FieldBase {
+    /// Creates the base of a new field.
+    pub fn new(name: impl Into<String>) -> Self {
+        FieldBase {
+            name: name.into(),
+            ..FieldBase::default()
+        }
+    }
+}
+
+/// A `text` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TextField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The minimum number of characters, `0` for no limit.
+    pub min: u64,
+    /// The maximum number of characters, `0` for the default limit.
+    pub max: u64,
+    /// The regular expression the value must match.
+    pub pattern: String,
+    /// The regular expression used to generate the value when it's empty.
+    pub autogenerate_pattern: String,
+    /// Whether this field is the primary key (only `id` is).
+    pub primary_key: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An `editor` field, containing HTML.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EditorField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The maximum size of the value in bytes, `0` for the default limit.
+    pub max_size: u64,
+    /// Whether the URLs are converted to relative URLs.
+    #[serde(rename = "convertURLs")]
+    pub convert_urls: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `number` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NumberField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be zero.
+    pub required: bool,
+    /// The minimum value.
+    pub min: Option<f64>,
+    /// The maximum value.
+    pub max: Option<f64>,
+    /// Whether the value must be an integer.
+    pub only_int: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `bool` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoolField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value must be `true`.
+    pub required: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An `email` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EmailField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The domains that are not allowed.
+    pub except_domains: Option<Vec<String>>,
+    /// The only domains that are allowed.
+    pub only_domains: Option<Vec<String>>,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `url` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct UrlField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The domains that are not allowed.
+    pub except_domains: Option<Vec<String>>,
+    /// The only domains that are allowed.
+    pub only_domains: Option<Vec<String>>,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `date` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The minimum date, empty for no limit.
+    pub min: String,
+    /// The maximum date, empty for no limit.
+    pub max: String,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An `autodate` field, set automatically by the server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AutodateField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the date is set when the record is created.
+    pub on_create: bool,
+    /// Whether the date is set every time the record is updated.
+    pub on_update: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `select` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SelectField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The allowed values.
+    pub values: Vec<String>,
+    /// The maximum number of selected values.
+    /// The value is a single string if it's `1`, an array of strings otherwise.
+    pub max_select: u64,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `file` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FileField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The maximum number of files.
+    /// The value is a single file name if it's `1`, an array of file names otherwise.
+    pub max_select: u64,
+    /// The maximum size of a file in bytes, `0` for the default limit.
+    pub max_size: u64,
+    /// The allowed MIME types, empty to allow all of them.
+    pub mime_types: Vec<String>,
+    /// The allowed thumb sizes (`100x100`, `0x100`, etc.).
+    pub thumbs: Vec<String>,
+    /// Whether the files require a token to be accessed.
+    pub protected: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `relation` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RelationField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The ID of the related collection.
+    pub collection_id: String,
+    /// Whether the record is deleted when the related record is deleted.
+    pub cascade_delete: bool,
+    /// The minimum number of related records.
+    pub min_select: u64,
+    /// The maximum number of related records.
+    /// The value is a single ID if it's `1`, an array of IDs otherwise.
+    pub max_select: u64,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `json` field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct JsonField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The maximum size of the value in bytes, `0` for the default limit.
+    pub max_size: u64,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `password` field, which is only found in `auth` collections.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be empty.
+    pub required: bool,
+    /// The minimum number of characters.
+    pub min: u64,
+    /// The maximum number of characters, `0` for the default limit.
+    pub max: u64,
+    /// The regular expression the value must match.
+    pub pattern: String,
+    /// The cost of the bcrypt hash, `0` for the default cost.
+    pub cost: u64,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A `geoPoint` field, holding a longitude and a latitude.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeoPointField {
+    /// The common properties of the field.
+    #[serde(flatten)]
+    pub base: FieldBase,
+    /// Whether the value cannot be the `{lon: 0, lat: 0}` point.
+    pub required: bool,
+    /// The other properties of the field, which aren't modeled by this struct.
+    /// They are kept so that they aren't lost when the field is sent back to the server.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A field of a collection, as found in the `fields` array
+/// of the collections returned by the API, or of `pb_schema.json`.
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// let field = Field::Number(NumberField {
+///     base: FieldBase::new("price"),
+///     min: Some(0.0),
+///     ..NumberField::default()
+/// });
+/// assert_eq!(field.type_name(), "number");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Field {
+    /// A `text` field.
+    Text(TextField),
+    /// An `editor` field.
+    Editor(EditorField),
+    /// A `number` field.
+    Number(NumberField),
+    /// A `bool` field.
+    Bool(BoolField),
+    /// An `email` field.
+    Email(EmailField),
+    /// A `url` field.
+    Url(UrlField),
+    /// A `date` field.
+    Date(DateField),
+    /// An `autodate` field.
+    Autodate(AutodateField),
+    /// A `select` field.
+    Select(SelectField),
+    /// A `file` field.
+    File(FileField),
+    /// A `relation` field.
+    Relation(RelationField),
+    /// A `json` field.
+    Json(JsonField),
+    /// A `password` field.
+    Password(PasswordField),
+    /// A `geoPoint` field.
+    GeoPoint(GeoPointField),
+    /// A field whose type is unknown to this crate,
+    /// for example a custom field registered by a plugin.
+    /// It's kept as is, so that it isn't lost when sent back to the server.
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+impl Field {
+    /// The properties shared by all the fields.
+    /// Returns [Option::None] if the type of the field is unknown.
+    pub fn base(&self) -> Option<&FieldBase> {
+        match self {
+            Field::Text(field) => Some(&field.base),
+            Field::Editor(field) => Some(&field.base),
+            Field::Number(field) => Some(&field.base),
+            Field::Bool(field) => Some(&field.base),
+            Field::Email(field) => Some(&field.base),
+            Field::Url(field) => Some(&field.base),
+            Field::Date(field) => Some(&field.base),
+            Field::Autodate(field) => Some(&field.base),
+            Field::Select(field) => Some(&field.base),
+            Field::File(field) => Some(&field.base),
+            Field::Relation(field) => Some(&field.base),
+            Field::Json(field) => Some(&field.base),
+            Field::Password(field) => Some(&field.base),
+            Field::GeoPoint(field) => Some(&field.base),
+            Field::Unknown(_) => None,
+        }
+    }
+
+    /// The name of the field.
+    pub fn name(&self) -> &str {
+        match self {
+            Field::Unknown(value) => value.get("name").and_then(|x| x.as_str()).unwrap_or_default(),
+            _ => &self.base().unwrap().name,
+        }
+    }
+
+    /// The ID of the field.
+    pub fn id(&self) -> &str {
+        match self {
+            Field::Unknown(value) => value.get("id").and_then(|x| x.as_str()).unwrap_or_default(),
+            _ => &self.base().unwrap().id,
+        }
+    }
+
+    /// The type of the field, as named by PocketBase (`text`, `geoPoint`, etc.).
+    pub fn type_name(&self) -> &str {
+        match self {
+            Field::Text(_) => "text",
+            Field::Editor(_) => "editor",
+            Field::Number(_) => "number",
+            Field::Bool(_) => "bool",
+            Field::Email(_) => "email",
+            Field::Url(_) => "url",
+            Field::Date(_) => "date",
+            Field::Autodate(_) => "autodate",
+            Field::Select(_) => "select",
+            Field::File(_) => "file",
+            Field::Relation(_) => "relation",
+            Field::Json(_) => "json",
+            Field::Password(_) => "password",
+            Field::GeoPoint(_) => "geoPoint",
+            Field::Unknown(value) => value.get("type").and_then(|x| x.as_str()).unwrap_or_default(),
+        }
+    }
+}
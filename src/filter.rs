@@ -0,0 +1,352 @@
+//! Builds the `filter` expressions of the list requests,
+//! without having to escape the values by hand.
+//!
+//! ```rust
+//! # use pbrsdk::*;
+//! use pbrsdk::filter::field;
+//!
+//! let query = "it's";
+//! let filter = field("price").gt(10).and(field("name").like(query).or(field("tags").any_eq("sale")));
+//! assert_eq!(filter.to_string(), r"price > 10 && (name ~ 'it\'s' || tags ?= 'sale')");
+//! ```
+
+use std::fmt;
+use std::time::SystemTime;
+use serde_json::Value;
+use crate::datetime::PbDateTime;
+use crate::geo::GeoPoint;
+
+/// A value of a filter, already escaped.
+///
+/// Strings, arrays and dates are quoted, numbers and booleans are written as is,
+/// and [Option::None] becomes `null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterValue(String);
+
+impl FilterValue {
+    /// A value that is written as is, without being escaped,
+    /// such as another field (`@request.auth.id`) or a macro (`@now`).
+    /// Never give it a value coming from the user.
+    pub fn raw(expression: impl Into<String>) -> Self {
+        FilterValue(expression.into())
+    }
+
+    /// The escaped value, as written in the filter.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Escapes a JSON value the same way as the JavaScript SDK,
+/// except that backslashes are escaped too.
+fn escape(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(value) => value.to_string(),
+        Value::String(value) => quote(value),
+        Value::Array(_) | Value::Object(_) => quote(&value.to_string()),
+    }
+}
+
+/// Quotes a string. The backslashes are escaped first,
+/// otherwise a trailing one would escape the closing quote.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+impl From<Value> for FilterValue {
+    fn from(value: Value) -> Self {
+        FilterValue(escape(&value))
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        FilterValue(quote(value))
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        FilterValue(quote(&value))
+    }
+}
+
+impl From<&String> for FilterValue {
+    fn from(value: &String) -> Self {
+        FilterValue(quote(value))
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        FilterValue(value.to_string())
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for FilterValue {
+            fn from(value: $t) -> Self {
+                FilterValue::from(serde_json::json!(value))
+            }
+        })*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl From<SystemTime> for FilterValue {
+    fn from(value: SystemTime) -> Self {
+        FilterValue::from(PbDateTime::from(value))
+    }
+}
+
+impl From<PbDateTime> for FilterValue {
+    fn from(value: PbDateTime) -> Self {
+        FilterValue(quote(&value.to_string()))
+    }
+}
+
+impl<V: Into<FilterValue>> From<Option<V>> for FilterValue {
+    fn from(value: Option<V>) -> Self {
+        value.map(|x| x.into()).unwrap_or_else(|| FilterValue("null".to_string()))
+    }
+}
+
+impl<V: serde::Serialize> From<Vec<V>> for FilterValue {
+    fn from(value: Vec<V>) -> Self {
+        FilterValue::from(serde_json::to_value(value).unwrap_or_default())
+    }
+}
+
+impl<V: serde::Serialize> From<&[V]> for FilterValue {
+    fn from(value: &[V]) -> Self {
+        FilterValue::from(serde_json::to_value(value).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Single,
+    And,
+    Or,
+    Raw,
+}
+
+/// A filter expression, built from [field] and combined with [Filter::and] and [Filter::or].
+/// Parentheses are added where they are needed.
+///
+/// It's converted into the `filter` option with `.into()` or `.to_string()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expression: String,
+    kind: Kind,
+}
+
+impl Filter {
+    /// Starts a condition on a field, such as `price` or `author.name`.
+    pub fn field(name: impl Into<String>) -> FilterField {
+        field(name)
+    }
+
+    /// An expression that is used as is, without being escaped.
+    /// Use [bind] to insert values in it safely.
+    pub fn raw(expression: impl Into<String>) -> Self {
+        Filter {
+            expression: expression.into(),
+            kind: Kind::Raw,
+        }
+    }
+
+    fn operand(&self, kind: Kind) -> String {
+        if self.kind == Kind::Single || self.kind == kind {
+            self.expression.clone()
+        } else {
+            format!("({})", self.expression)
+        }
+    }
+
+    fn combine(self, other: Filter, kind: Kind, operator: &str) -> Filter {
+        Filter {
+            expression: format!("{} {} {}", self.operand(kind), operator, other.operand(kind)),
+            kind,
+        }
+    }
+
+    /// Both conditions must match (`&&`).
+    pub fn and(self, other: Filter) -> Filter {
+        self.combine(other, Kind::And, "&&")
+    }
+
+    /// At least one of the conditions must match (`||`).
+    pub fn or(self, other: Filter) -> Filter {
+        self.combine(other, Kind::Or, "||")
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl From<Filter> for String {
+    fn from(filter: Filter) -> Self {
+        filter.expression
+    }
+}
+
+impl From<Filter> for Option<String> {
+    fn from(filter: Filter) -> Self {
+        Some(filter.expression)
+    }
+}
+
+/// Starts a condition on a field, such as `price` or `author.name`.
+pub fn field(name: impl Into<String>) -> FilterField {
+    FilterField(name.into())
+}
+
+/// The distance in kilometers between a `geoPoint` field and a point (`geoDistance()`),
+/// to be compared with the operators of [FilterField].
+/// The coordinates of a [GeoPoint] are always finite, so the expression is always valid.
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// use pbrsdk::filter::geo_distance;
+///
+/// let filter = geo_distance("location", GeoPoint::new(2.35, 48.85).unwrap()).gt(10);
+/// assert_eq!(filter.to_string(), "geoDistance(location.lon, location.lat, 2.35, 48.85) > 10");
+/// ```
+pub fn geo_distance(field: impl Into<String>, point: GeoPoint) -> FilterField {
+    FilterField(geo_distance_expression(&field.into(), point))
+}
+
+pub(crate) fn geo_distance_expression(field: &str, point: GeoPoint) -> String {
+    format!("geoDistance({0}.lon, {0}.lat, {1}, {2})", field, FilterValue::from(point.lon()), FilterValue::from(point.lat()))
+}
+
+/// A field on which a condition is being built.
+///
+/// The `any_` methods are the "any of" operators (`?=`, `?~`, etc.),
+/// used with multiple values fields such as multiple relations or `:each` modifiers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterField(String);
+
+macro_rules! operators {
+    ($($(#[$doc:meta])* $name:ident => $operator:literal,)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(self, value: impl Into<FilterValue>) -> Filter {
+                self.compare($operator, value.into())
+            }
+        )*
+    };
+}
+
+impl FilterField {
+    fn compare(self, operator: &str, value: FilterValue) -> Filter {
+        Filter {
+            expression: format!("{} {} {}", self.0, operator, value),
+            kind: Kind::Single,
+        }
+    }
+
+    operators! {
+        /// Equal (`=`).
+        eq => "=",
+        /// Not equal (`!=`).
+        ne => "!=",
+        /// Greater than (`>`).
+        gt => ">",
+        /// Greater than or equal (`>=`).
+        gte => ">=",
+        /// Less than (`<`).
+        lt => "<",
+        /// Less than or equal (`<=`).
+        lte => "<=",
+        /// Like, contains (`~`). The value is wrapped in `%` if it doesn't contain any.
+        like => "~",
+        /// Not like, doesn't contain (`!~`).
+        not_like => "!~",
+        /// Any of the values is equal (`?=`).
+        any_eq => "?=",
+        /// Any of the values is not equal (`?!=`).
+        any_ne => "?!=",
+        /// Any of the values is greater than (`?>`).
+        any_gt => "?>",
+        /// Any of the values is greater than or equal (`?>=`).
+        any_gte => "?>=",
+        /// Any of the values is less than (`?<`).
+        any_lt => "?<",
+        /// Any of the values is less than or equal (`?<=`).
+        any_lte => "?<=",
+        /// Any of the values is like (`?~`).
+        any_like => "?~",
+        /// Any of the values is not like (`?!~`).
+        any_not_like => "?!~",
+    }
+
+    /// The field is null (`= null`).
+    pub fn is_null(self) -> Filter {
+        self.compare("=", FilterValue::raw("null"))
+    }
+
+    /// The field isn't null (`!= null`).
+    pub fn is_not_null(self) -> Filter {
+        self.compare("!=", FilterValue::raw("null"))
+    }
+
+    /// The `geoPoint` field is at most `km` kilometers away from the point.
+    /// See [geo_distance] for the other comparisons.
+    ///
+    /// ```rust
+    /// # use pbrsdk::*;
+    /// let filter = Filter::field("location").within_km(GeoPoint::new(2.35, 48.85).unwrap(), 5.5);
+    /// assert_eq!(filter.to_string(), "geoDistance(location.lon, location.lat, 2.35, 48.85) <= 5.5");
+    /// ```
+    pub fn within_km(self, point: GeoPoint, km: f64) -> Filter {
+        geo_distance(self.0, point).lte(km)
+    }
+}
+
+/// Replaces the `{:key}` placeholders of the expression with the escaped values of the params,
+/// like `pb.filter()` in the JavaScript SDK.
+/// The params are usually given with `serde_json::json!`.
+///
+/// ```rust
+/// # use pbrsdk::filter::bind;
+/// let filter = bind("title ~ {:title} && created >= {:date}", serde_json::json!({ "title": "it's", "date": "2026-01-28 10:02:31.589Z" }));
+/// assert_eq!(filter, r"title ~ 'it\'s' && created >= '2026-01-28 10:02:31.589Z'");
+/// ```
+pub fn bind(expression: impl Into<String>, params: Value) -> String {
+    let expression = expression.into();
+    let Value::Object(params) = params else { return expression };
+    // The expression is scanned once, so the inserted values are never scanned again.
+    let mut result = String::with_capacity(expression.len());
+    let mut rest = expression.as_str();
+    while let Some(start) = rest.find("{:") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match rest.find('}').and_then(|end| Some((end, params.get(&rest[2..end])?))) {
+            Some((end, value)) => {
+                result.push_str(&escape(value));
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push_str("{:");
+                rest = &rest[2..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
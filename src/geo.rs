@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The error returned when a coordinate of a [GeoPoint] is out of range, NaN or infinite.
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("Invalid coordinates: ({lon}, {lat})")]
+pub struct GeoPointError {
+    lon: f64,
+    lat: f64,
+}
+
+/// The value of a `geoPoint` field: a longitude and a latitude, in degrees.
+/// The longitude is between -180 and 180, and the latitude between -90 and 90.
+///
+/// The records can be filtered and sorted by their distance to a point,
+/// see [FilterField::within_km](crate::FilterField::within_km),
+/// [filter::geo_distance](crate::filter::geo_distance) and [Sort::nearest](crate::Sort::nearest).
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// let paris = GeoPoint::new(2.3522, 48.8566).unwrap();
+/// let point: GeoPoint = serde_json::from_str(r#"{"lon":2.3522,"lat":48.8566}"#).unwrap();
+/// assert_eq!(point, paris);
+/// assert!(GeoPoint::new(f64::NAN, 48.8566).is_err());
+/// assert!(GeoPoint::new(2.3522, 91.0).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawGeoPoint")]
+pub struct GeoPoint {
+    lon: f64,
+    lat: f64,
+}
+
+#[derive(Deserialize)]
+struct RawGeoPoint {
+    lon: f64,
+    lat: f64,
+}
+
+impl TryFrom<RawGeoPoint> for GeoPoint {
+    type Error = GeoPointError;
+
+    fn try_from(point: RawGeoPoint) -> Result<Self, Self::Error> {
+        GeoPoint::new(point.lon, point.lat)
+    }
+}
+
+impl GeoPoint {
+    /// Creates a point from its longitude and its latitude, in this order, like PocketBase.
+    /// Fails if the longitude isn't between -180 and 180, or if the latitude isn't between -90 and 90.
+    pub fn new(lon: f64, lat: f64) -> Result<Self, GeoPointError> {
+        if (-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat) {
+            Ok(GeoPoint { lon, lat })
+        } else {
+            Err(GeoPointError { lon, lat })
+        }
+    }
+
+    /// The longitude, between -180 and 180.
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// The latitude, between -90 and 90.
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+}
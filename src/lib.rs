@@ -8,7 +8,8 @@
 //! If this crate is lacking a feature, you can simply make your own HTTP requests
 //! and use the [AuthStore] instance to access the bearer token.
 //!
-//! ```rust
+//! ```rust,no_run
+//! # use pbrsdk::*;
 //! #[tokio::main]
 //!  async fn main() {
 //!     // to use a custom user type then call PocketBase::<CustomUserType>::new();
@@ -29,15 +30,38 @@
 mod pocketbase;
 mod error;
 mod auth;
+#[cfg(test)]
 mod tests;
 mod common;
 mod services;
 mod cookies;
+mod backends;
+mod fields;
+pub mod filter;
+mod query;
+mod expand;
+pub mod schema;
+mod uploads;
+pub mod datetime;
+mod geo;
 
 pub use pbrsdk_macros::base_system_fields;
 pub use pocketbase::*;
 pub use error::*;
 pub use common::*;
 pub use auth::*;
+pub use backends::*;
+pub use fields::*;
+pub use filter::{Filter, FilterField, FilterValue};
+pub use query::{Fields, Sort};
+pub use expand::{Expand, Expandable, Relation, MAX_EXPAND_DEPTH};
+pub use datetime::{PbDateTime, PbDateTimeError};
+pub use geo::{GeoPoint, GeoPointError};
+pub use schema::{RuleKind, SchemaChange, SchemaDiff};
+pub use uploads::{FileUpload, RecordFiles};
+pub use cookies::{CookieOptions, SameSite};
 pub use services::record_service::*;
 pub use services::collection_service::*;
+pub use services::realtime_service::*;
+pub use services::batch_service::*;
+pub use services::file_service::*;
@@ -1,10 +1,22 @@
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use reqwest::{Client};
+use reqwest::header::HeaderMap;
 use serde::de::DeserializeOwned;
-use crate::auth::{AuthStore, DefaultAuthRecord};
+use tokio::sync::watch;
+use crate::auth::{is_token_expiring, is_token_refreshable, AuthStore, DefaultAuthRecord};
 use crate::CollectionService;
 use crate::services::record_service::RecordService;
+use crate::services::realtime_service::{RealtimeClient, RealtimeService};
+use crate::services::batch_service::BatchService;
+use crate::services::file_service::FileService;
 use crate::error::ApiError;
+use crate::backends::{AuthData, AuthStoreBackend, InMemoryBackend};
+use crate::cookies::cookie_auth_data;
+
+/// A callback notified of the changes of the [AuthStore].
+type AuthListener<T> = Arc<dyn Fn(&AuthStore<T>) + Send + Sync>;
 
 /// Creates a pocketbase instance from which requests to the server can be made.
 /// It will also store essential pieces of information relative to the authentication.
@@ -16,8 +28,133 @@ where T: DeserializeOwned + Clone {
 pub(crate) struct PocketBaseRef<T = DefaultAuthRecord>
 where T: DeserializeOwned + Clone {
     pub(crate) auth_store: Arc<Mutex<AuthStore<T>>>,
+    pub(crate) backend: Arc<dyn AuthStoreBackend>,
     pub(crate) base_url: String,
     pub(crate) client: Client,
+    pub(crate) realtime: RealtimeClient,
+    /// When set, the token is refreshed before a request
+    /// if it expires within this window.
+    pub(crate) auto_refresh: Mutex<Option<Duration>>,
+    /// Prevents concurrent requests from refreshing the token at the same time.
+    pub(crate) refresh_lock: tokio::sync::Mutex<()>,
+    /// The number of writes made to the backend. The writes are made while holding this lock,
+    /// so that they are numbered in the order they are made.
+    pub(crate) backend_writes: Arc<Mutex<u64>>,
+    /// The number of the backend write whose data is in the [AuthStore].
+    /// It's only changed while holding the lock of the [AuthStore].
+    pub(crate) applied_backend_write: AtomicU64,
+    pub(crate) auth_listeners: Mutex<Vec<(u64, AuthListener<T>)>>,
+    pub(crate) next_auth_listener_id: AtomicU64,
+    pub(crate) auth_watch: watch::Sender<AuthStore<T>>,
+}
+
+impl<T> PocketBaseRef<T>
+where T: DeserializeOwned + Clone {
+    /// Builds the headers holding the bearer token of the [AuthStore], if there is one.
+    pub(crate) fn auth_headers(&self) -> HeaderMap {
+        let token = self.auth_store.lock().unwrap().token.clone();
+        let mut headers: HeaderMap = HeaderMap::new();
+        if let Some(token) = token {
+            headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+        }
+        headers
+    }
+
+    /// Persists the token and the record of a successful authentication with the backend,
+    /// then stores them. Nothing changes if the record is invalid or if the backend fails.
+    /// The backend runs on a blocking thread, without locking the [AuthStore].
+    pub(crate) async fn save_auth(&self, token: String, record: serde_json::Value) -> Result<(), ApiError> {
+        let data = AuthData { token, record };
+        let mut updated = AuthStore::default();
+        updated.load_data(&data)?;
+        let backend = self.backend.clone();
+        let backend_writes = self.backend_writes.clone();
+        let write = tokio::task::spawn_blocking(move || write_backend(backend.as_ref(), &backend_writes, Some(&data))).await
+            .map_err(std::io::Error::other)??;
+        self.apply_auth(write, updated);
+        Ok(())
+    }
+
+    /// Same as [PocketBaseRef::save_auth], with the backend running on the current thread.
+    pub(crate) fn save_auth_blocking(&self, token: String, record: serde_json::Value) -> Result<(), ApiError> {
+        let data = AuthData { token, record };
+        let mut updated = AuthStore::default();
+        updated.load_data(&data)?;
+        let write = write_backend(self.backend.as_ref(), &self.backend_writes, Some(&data))?;
+        self.apply_auth(write, updated);
+        Ok(())
+    }
+
+    /// Replaces the record of the authenticated user, keeping the same token.
+    pub(crate) async fn save_auth_record(&self, record: serde_json::Value) -> Result<(), ApiError> {
+        let token = self.auth_store.lock().unwrap().token.clone();
+        match token {
+            Some(token) => self.save_auth(token, record).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Clears the [AuthStore] and the data persisted by the backend.
+    /// The backend runs on a blocking thread, without locking the [AuthStore].
+    pub(crate) async fn clear_auth(&self) -> Result<(), ApiError> {
+        let backend = self.backend.clone();
+        let backend_writes = self.backend_writes.clone();
+        let write = tokio::task::spawn_blocking(move || write_backend(backend.as_ref(), &backend_writes, None)).await
+            .map_err(std::io::Error::other)??;
+        self.apply_auth(write, AuthStore::default());
+        Ok(())
+    }
+
+    /// Same as [PocketBaseRef::clear_auth], with the backend running on the current thread.
+    pub(crate) fn clear_auth_blocking(&self) -> Result<(), ApiError> {
+        let write = write_backend(self.backend.as_ref(), &self.backend_writes, None)?;
+        self.apply_auth(write, AuthStore::default());
+        Ok(())
+    }
+
+    /// Replaces the [AuthStore] with the data of the given backend write,
+    /// unless the data of a later write is already stored.
+    fn apply_auth(&self, write: u64, updated: AuthStore<T>) {
+        {
+            let mut auth_store = self.auth_store.lock().unwrap();
+            if self.applied_backend_write.load(Ordering::Relaxed) > write {
+                return;
+            }
+            self.applied_backend_write.store(write, Ordering::Relaxed);
+            *auth_store = updated;
+        }
+        self.notify_auth_change();
+    }
+
+    /// Sends a snapshot of the [AuthStore] to the listeners and to the watchers.
+    pub(crate) fn notify_auth_change(&self) {
+        let snapshot = self.auth_store.lock().unwrap().clone();
+        self.auth_watch.send_replace(snapshot.clone());
+        let listeners = self.auth_listeners.lock().unwrap().iter()
+            .map(|(_, listener)| listener.clone())
+            .collect::<Vec<AuthListener<T>>>();
+        for listener in listeners {
+            listener(&snapshot);
+        }
+    }
+
+    /// Checks if the current token is still valid but expires within the given window.
+    pub(crate) fn needs_refresh(&self, window: Duration) -> bool {
+        let store = self.auth_store.lock().unwrap();
+        store.is_valid() && is_token_refreshable(store.token.as_ref().unwrap()) && is_token_expiring(store.token.as_ref().unwrap(), window)
+    }
+}
+
+/// Saves the data with the backend, or clears it if there is none,
+/// and returns the number of this write.
+fn write_backend(backend: &dyn AuthStoreBackend, backend_writes: &Mutex<u64>, data: Option<&AuthData>) -> Result<u64, ApiError> {
+    let mut writes = backend_writes.lock().unwrap();
+    match data {
+        Some(data) => backend.save(data)?,
+        None => backend.clear()?,
+    }
+    *writes += 1;
+    Ok(*writes)
 }
 
 impl<T> PocketBase<T>
@@ -32,14 +169,118 @@ where T: DeserializeOwned + Clone {
     /// Creates a new instance of [PocketBase].
     pub fn new(base_url: impl Into<String>) -> Result<Self, ApiError> {
         let client = Client::new();
-        let url = base_url.into().strip_suffix("/").unwrap().to_owned();
-        Ok(Self {
+        let url = base_url.into().trim_end_matches('/').to_owned();
+        Ok(Self::from_client(url, client, Arc::new(InMemoryBackend::default())))
+    }
+
+    /// Creates a new instance of [PocketBase] whose authentication is persisted by the given backend.
+    /// If the backend holds the data of a previous authentication, it's loaded into the [AuthStore].
+    /// Data that cannot be loaded, such as a corrupted file or a record that no longer matches `T`,
+    /// is cleared and the instance starts unauthenticated. Only I/O errors are returned.
+    ///
+    /// ```rust,no_run
+    /// # use pbrsdk::*;
+    /// let pb = PocketBase::<DefaultAuthRecord>::with_backend("http://localhost:8091/", JsonFileBackend::new("auth.json")).unwrap();
+    /// println!("still authenticated : {}", pb.auth_store().is_valid());
+    /// ```
+    pub fn with_backend(base_url: impl Into<String>, backend: impl AuthStoreBackend + 'static) -> Result<Self, ApiError> {
+        let client = Client::new();
+        let url = base_url.into().trim_end_matches('/').to_owned();
+        let pb = Self::from_client(url, client, Arc::new(backend));
+        let loaded = match pb.inner.backend.load() {
+            Ok(None) => return Ok(pb),
+            Ok(Some(data)) => pb.inner.auth_store.lock().unwrap().load_data(&data).is_ok(),
+            Err(err @ ApiError::Io(_)) => return Err(err),
+            Err(_) => false,
+        };
+        if loaded {
+            pb.inner.notify_auth_change();
+        } else {
+            pb.inner.backend.clear()?;
+        }
+        Ok(pb)
+    }
+
+    /// Clears the [AuthStore], which logs the user out,
+    /// and removes the data persisted by the backend, on the current thread.
+    pub fn clear_auth_store(&self) -> Result<(), ApiError> {
+        self.inner.clear_auth_blocking()
+    }
+
+    /// Registers a callback that is called every time the [AuthStore] changes:
+    /// after an authentication, a refresh of the token, an update of the current user's record,
+    /// or when it's cleared. The callback receives a snapshot of the store.
+    ///
+    /// Returns an ID that can be given to [PocketBase::remove_auth_listener].
+    pub fn on_auth_change<F>(&self, callback: F) -> u64
+    where F: Fn(&AuthStore<T>) + Send + Sync + 'static {
+        let id = self.inner.next_auth_listener_id.fetch_add(1, Ordering::Relaxed);
+        self.inner.auth_listeners.lock().unwrap().push((id, Arc::new(callback)));
+        id
+    }
+
+    /// Removes a callback that was registered with [PocketBase::on_auth_change].
+    pub fn remove_auth_listener(&self, id: u64) {
+        self.inner.auth_listeners.lock().unwrap().retain(|(listener_id, _)| *listener_id != id);
+    }
+
+    /// Returns a receiver that always holds the latest snapshot of the [AuthStore].
+    /// Use [watch::Receiver::changed] to wait for the next change.
+    pub fn watch_auth(&self) -> watch::Receiver<AuthStore<T>> {
+        self.inner.auth_watch.subscribe()
+    }
+
+    /// Loads the [AuthStore] from the `pb_auth` cookie of a `Cookie` or `Set-Cookie` header,
+    /// and persists it with the backend, on the current thread.
+    /// The store is cleared if there is no such cookie, or if it's invalid.
+    /// See [AuthStore::load_from_cookie].
+    pub fn load_auth_from_cookie(&self, header: &str) -> Result<(), ApiError> {
+        let result = match cookie_auth_data(header) {
+            Ok(Some(data)) => self.inner.save_auth_blocking(data.token, data.record),
+            Ok(None) => return self.inner.clear_auth_blocking(),
+            Err(err) => Err(err),
+        };
+        match result {
+            // An invalid cookie is cleared like a missing one, but the error is still returned.
+            Err(err @ ApiError::Json(_)) => {
+                self.inner.clear_auth_blocking()?;
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    /// Creates a new instance of [PocketBase], with an empty [AuthStore],
+    /// that reuses an existing HTTP client. The base URL must not end with a slash.
+    pub(crate) fn from_client(base_url: String, client: Client, backend: Arc<dyn AuthStoreBackend>) -> Self {
+        Self {
             inner: Arc::new(PocketBaseRef {
-                client: client.clone(),
-                base_url: url.clone(),
+                client,
+                base_url,
                 auth_store: Arc::new(Mutex::new(AuthStore::default())),
+                backend,
+                realtime: RealtimeClient::default(),
+                auto_refresh: Mutex::new(None),
+                refresh_lock: tokio::sync::Mutex::new(()),
+                backend_writes: Arc::new(Mutex::new(0)),
+                applied_backend_write: AtomicU64::new(0),
+                auth_listeners: Mutex::new(Vec::new()),
+                next_auth_listener_id: AtomicU64::new(0),
+                auth_watch: watch::Sender::new(AuthStore::default()),
             })
-        })
+        }
+    }
+
+    /// Enables the automatic renewal of the token: before each request,
+    /// if the token expires within the given window, it is refreshed first.
+    /// It's disabled by default.
+    pub fn enable_auto_refresh(&self, window: Duration) {
+        *self.inner.auto_refresh.lock().unwrap() = Some(window);
+    }
+
+    /// Disables the automatic renewal of the token.
+    pub fn disable_auto_refresh(&self) {
+        *self.inner.auto_refresh.lock().unwrap() = None;
     }
 
     /// Creates an instance of [RecordService] that you will later be able to fetch.
@@ -58,6 +299,43 @@ where T: DeserializeOwned + Clone {
             pb: self.inner.clone(),
         }
     }
+
+    /// Returns a [RealtimeService] giving access to the Server-Sent Events of PocketBase.
+    /// All instances share the same connection.
+    pub fn realtime(&self) -> RealtimeService<T> {
+        RealtimeService {
+            pb: self.inner.clone(),
+        }
+    }
+
+    /// Replaces the `{:key}` placeholders of a filter expression with the escaped values of the params.
+    /// See [filter::bind](crate::filter::bind), and [Filter](crate::Filter) to build the whole expression instead.
+    ///
+    /// ```rust
+    /// # use pbrsdk::*;
+    /// let pb = PocketBase::default("http://localhost:8091/").unwrap();
+    /// let filter = pb.filter("name = {:name} && price > {:price}", serde_json::json!({ "name": "O'Neil", "price": 10 }));
+    /// assert_eq!(filter, r"name = 'O\'Neil' && price > 10");
+    /// ```
+    pub fn filter(&self, expression: impl Into<String>, params: serde_json::Value) -> String {
+        crate::filter::bind(expression, params)
+    }
+
+    /// Returns a [FileService], building the URLs of the files of the records.
+    pub fn files(&self) -> FileService<T> {
+        FileService {
+            pb: self.inner.clone(),
+        }
+    }
+
+    /// Creates a [BatchService], queuing record operations
+    /// to send them in a single transaction.
+    pub fn create_batch(&self) -> BatchService<T> {
+        BatchService {
+            pb: self.inner.clone(),
+            requests: Vec::new(),
+        }
+    }
 }
 
 impl PocketBase<DefaultAuthRecord> {
@@ -0,0 +1,168 @@
+use std::fmt;
+use crate::filter::geo_distance_expression;
+use crate::geo::GeoPoint;
+
+/// Builds the `sort` option of the list requests.
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// let sort = Sort::desc("created").then_asc("name").then_desc(Sort::ROWID);
+/// assert_eq!(sort.to_string(), "-created,name,-@rowid");
+/// let options = ListOptions { sort: sort.into(), ..ListOptions::default() };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sort(Vec<String>);
+
+impl Sort {
+    /// The `@random` sort field, which shuffles the records.
+    pub const RANDOM: &'static str = "@random";
+    /// The `@rowid` sort field, which is the insertion order of the records.
+    pub const ROWID: &'static str = "@rowid";
+
+    /// Sorts by a field in ascending order.
+    pub fn asc(field: impl Into<String>) -> Self {
+        Sort(vec![field.into()])
+    }
+
+    /// Sorts by a field in descending order.
+    pub fn desc(field: impl Into<String>) -> Self {
+        Sort(vec![format!("-{}", field.into())])
+    }
+
+    /// Sorts the records randomly.
+    pub fn random() -> Self {
+        Sort::asc(Sort::RANDOM)
+    }
+
+    /// Then sorts by another field in ascending order.
+    pub fn then_asc(mut self, field: impl Into<String>) -> Self {
+        self.0.push(field.into());
+        self
+    }
+
+    /// Then sorts by another field in descending order.
+    pub fn then_desc(mut self, field: impl Into<String>) -> Self {
+        self.0.push(format!("-{}", field.into()));
+        self
+    }
+
+    /// Then sorts the records randomly.
+    pub fn then_random(self) -> Self {
+        self.then_asc(Sort::RANDOM)
+    }
+
+    /// Sorts by the distance between a `geoPoint` field and a point, the nearest first.
+    ///
+    /// ```rust
+    /// # use pbrsdk::*;
+    /// let sort = Sort::nearest("location", GeoPoint::new(2.35, 48.85).unwrap());
+    /// assert_eq!(sort.to_string(), "geoDistance(location.lon, location.lat, 2.35, 48.85)");
+    /// ```
+    pub fn nearest(field: impl Into<String>, point: GeoPoint) -> Self {
+        Sort::asc(geo_distance_expression(&field.into(), point))
+    }
+
+    /// Sorts by the distance between a `geoPoint` field and a point, the farthest first.
+    pub fn farthest(field: impl Into<String>, point: GeoPoint) -> Self {
+        Sort::desc(geo_distance_expression(&field.into(), point))
+    }
+
+    /// Then sorts by the distance between a `geoPoint` field and a point, the nearest first.
+    pub fn then_nearest(self, field: impl Into<String>, point: GeoPoint) -> Self {
+        self.then_asc(geo_distance_expression(&field.into(), point))
+    }
+
+    /// Then sorts by the distance between a `geoPoint` field and a point, the farthest first.
+    pub fn then_farthest(self, field: impl Into<String>, point: GeoPoint) -> Self {
+        self.then_desc(geo_distance_expression(&field.into(), point))
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(","))
+    }
+}
+
+impl From<Sort> for String {
+    fn from(sort: Sort) -> Self {
+        sort.to_string()
+    }
+}
+
+impl From<Sort> for Option<String> {
+    fn from(sort: Sort) -> Self {
+        Some(sort.to_string())
+    }
+}
+
+/// Builds the `fields` option, selecting the fields returned by the server.
+///
+/// ```rust
+/// # use pbrsdk::*;
+/// let fields = Fields::new()
+///     .field("id")
+///     .excerpt("content", 200, true)
+///     .expand("author", ["name"])
+///     .expand("author.company", ["*"]);
+/// assert_eq!(fields.to_string(), "id,content:excerpt(200,true),expand.author.name,expand.author.expand.company.*");
+/// let options = ViewOptions { fields: fields.into(), ..ViewOptions::default() };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fields(Vec<String>);
+
+impl Fields {
+    /// Selects no field yet, which means all of them.
+    pub fn new() -> Self {
+        Fields::default()
+    }
+
+    /// Selects a field, or a path such as `expand.author.name`, or `*` for all the fields.
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.0.push(field.into());
+        self
+    }
+
+    /// Selects a text field, keeping only its first `max_length` characters, without the HTML tags.
+    /// If `with_ellipsis` is true, `...` is appended to the truncated text.
+    pub fn excerpt(mut self, field: impl Into<String>, max_length: u64, with_ellipsis: bool) -> Self {
+        self.0.push(format!("{}:excerpt({},{})", field.into(), max_length, with_ellipsis));
+        self
+    }
+
+    /// Selects fields of an expanded relation.
+    /// The relation can be nested, such as `author.company`,
+    /// which selects `expand.author.expand.company.FIELD`.
+    pub fn expand<I, S>(mut self, relation: impl Into<String>, fields: I) -> Self
+    where I: IntoIterator<Item = S>, S: Into<String> {
+        let path = relation.into().split('.').map(|x| format!("expand.{}", x)).collect::<Vec<_>>().join(".");
+        for field in fields {
+            self.0.push(format!("{}.{}", path, field.into()));
+        }
+        self
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Fields {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Fields(iter.into_iter().map(|x| x.into()).collect())
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(","))
+    }
+}
+
+impl From<Fields> for String {
+    fn from(fields: Fields) -> Self {
+        fields.to_string()
+    }
+}
+
+impl From<Fields> for Option<String> {
+    fn from(fields: Fields) -> Self {
+        if fields.0.is_empty() { None } else { Some(fields.to_string()) }
+    }
+}
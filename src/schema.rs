@@ -0,0 +1,354 @@
+//! Compares collection definitions, for example to check what
+//! [CollectionService::import](crate::CollectionService::import) would change on a server.
+//!
+//! ```rust
+//! # use pbrsdk::*;
+//! let remote = CollectionModel::from_file("pb_schema.json").unwrap();
+//! let mut local = remote.clone();
+//! local.retain(|x| x.name != "articles");
+//! let diff = schema::diff(&local, &remote);
+//! assert_eq!(diff.changes, vec![SchemaChange::CollectionRemoved { name: "articles".to_string() }]);
+//! println!("{}", diff);
+//! ```
+
+use std::fmt;
+use serde_json::Value;
+use crate::fields::Field;
+use crate::services::collection_service::{CollectionModel, CollectionType};
+
+/// An API rule of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// `listRule`
+    List,
+    /// `viewRule`
+    View,
+    /// `createRule`
+    Create,
+    /// `updateRule`
+    Update,
+    /// `deleteRule`
+    Delete,
+    /// `authRule`, only found in `auth` collections.
+    Auth,
+    /// `manageRule`, only found in `auth` collections.
+    Manage,
+}
+
+impl RuleKind {
+    const ALL: [RuleKind; 7] = [RuleKind::List, RuleKind::View, RuleKind::Create, RuleKind::Update, RuleKind::Delete, RuleKind::Auth, RuleKind::Manage];
+
+    /// The name of the rule, as found in the JSON of a collection.
+    pub fn key(&self) -> &'static str {
+        match self {
+            RuleKind::List => "listRule",
+            RuleKind::View => "viewRule",
+            RuleKind::Create => "createRule",
+            RuleKind::Update => "updateRule",
+            RuleKind::Delete => "deleteRule",
+            RuleKind::Auth => "authRule",
+            RuleKind::Manage => "manageRule",
+        }
+    }
+
+    fn of(&self, collection: &CollectionModel) -> Option<String> {
+        match self {
+            RuleKind::List => collection.list_rule.clone(),
+            RuleKind::View => collection.view_rule.clone(),
+            RuleKind::Create => collection.create_rule.clone(),
+            RuleKind::Update => collection.update_rule.clone(),
+            RuleKind::Delete => collection.delete_rule.clone(),
+            RuleKind::Auth | RuleKind::Manage => collection.options.get(self.key()).and_then(|x| x.as_str()).map(|x| x.to_string()),
+        }
+    }
+}
+
+/// A single difference between two sets of collections.
+/// The collections and fields are designated by their name in the local definitions,
+/// except for the removed ones, which only exist remotely.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaChange {
+    /// The collection only exists locally.
+    CollectionAdded {
+        /// The name of the collection.
+        name: String,
+    },
+    /// The collection only exists remotely.
+    /// It's only deleted by an import if `delete_missing` is true.
+    CollectionRemoved {
+        /// The name of the collection.
+        name: String,
+    },
+    /// The collection has the same ID but a different name.
+    CollectionRenamed {
+        /// The remote name of the collection.
+        from: String,
+        /// The local name of the collection.
+        to: String,
+    },
+    /// The type of the collection changed.
+    TypeChanged {
+        /// The name of the collection.
+        collection: String,
+        /// The remote type.
+        before: CollectionType,
+        /// The local type.
+        after: CollectionType,
+    },
+    /// The SQL query of a `view` collection changed.
+    ViewQueryChanged {
+        /// The name of the collection.
+        collection: String,
+        /// The remote query.
+        before: Option<String>,
+        /// The local query.
+        after: Option<String>,
+    },
+    /// An option depending on the type of the collection changed, such as `passwordAuth`.
+    /// [Option::None] means that the option isn't set.
+    OptionChanged {
+        /// The name of the collection.
+        collection: String,
+        /// The name of the option, as found in the JSON of a collection.
+        key: String,
+        /// The remote value.
+        before: Option<Value>,
+        /// The local value.
+        after: Option<Value>,
+    },
+    /// An API rule changed. [Option::None] means that only superusers are allowed.
+    RuleChanged {
+        /// The name of the collection.
+        collection: String,
+        /// The rule that changed.
+        rule: RuleKind,
+        /// The remote rule.
+        before: Option<String>,
+        /// The local rule.
+        after: Option<String>,
+    },
+    /// The field only exists locally.
+    FieldAdded {
+        /// The name of the collection.
+        collection: String,
+        /// The local field.
+        field: Field,
+    },
+    /// The field only exists remotely.
+    /// It's only deleted by an import if `delete_missing` is true.
+    FieldRemoved {
+        /// The name of the collection.
+        collection: String,
+        /// The remote field.
+        field: Field,
+    },
+    /// The field exists on both sides, but its type, name or options differ.
+    FieldChanged {
+        /// The name of the collection.
+        collection: String,
+        /// The remote field.
+        before: Field,
+        /// The local field.
+        after: Field,
+    },
+    /// The index only exists locally.
+    IndexAdded {
+        /// The name of the collection.
+        collection: String,
+        /// The SQL statement creating the index.
+        index: String,
+    },
+    /// The index only exists remotely.
+    IndexRemoved {
+        /// The name of the collection.
+        collection: String,
+        /// The SQL statement creating the index.
+        index: String,
+    },
+}
+
+/// The differences between two sets of collections, returned by [diff].
+/// It's displayed as a human-readable report, with one change per line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    /// The changes, grouped by collection.
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Whether both sets of collections are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Compares the `local` collections, which are the desired state,
+/// with the `remote` ones, which are the current state, usually fetched with
+/// [CollectionService::get_full_list](crate::CollectionService::get_full_list).
+///
+/// Collections and fields are matched by ID, like an import does,
+/// and by name when the local definition has no ID.
+pub fn diff(local: &[CollectionModel], remote: &[CollectionModel]) -> SchemaDiff {
+    let mut changes = Vec::new();
+    let mut matched_remote = vec![false; remote.len()];
+    for collection in local {
+        let index = find_match(remote, &matched_remote, |x| &x.id, |x| &x.name, &collection.id, &collection.name);
+        match index {
+            Some(index) => {
+                matched_remote[index] = true;
+                diff_collection(collection, &remote[index], &mut changes);
+            }
+            None => changes.push(SchemaChange::CollectionAdded { name: collection.name.clone() }),
+        }
+    }
+    for (collection, _) in remote.iter().zip(matched_remote).filter(|(_, matched)| !matched) {
+        changes.push(SchemaChange::CollectionRemoved { name: collection.name.clone() });
+    }
+    SchemaDiff { changes }
+}
+
+/// Finds the first unmatched item with the same ID, or with the same name if there is no ID.
+fn find_match<I>(items: &[I], matched: &[bool], id: impl Fn(&I) -> &str, name: impl Fn(&I) -> &str, wanted_id: &str, wanted_name: &str) -> Option<usize> {
+    let unmatched = || items.iter().enumerate().filter(|(i, _)| !matched[*i]);
+    if !wanted_id.is_empty()
+        && let Some((index, _)) = unmatched().find(|(_, x)| id(x) == wanted_id) {
+        return Some(index);
+    }
+    unmatched().find(|(_, x)| name(x) == wanted_name && (wanted_id.is_empty() || id(x).is_empty())).map(|(i, _)| i)
+}
+
+fn diff_collection(local: &CollectionModel, remote: &CollectionModel, changes: &mut Vec<SchemaChange>) {
+    let collection = &local.name;
+    if local.name != remote.name {
+        changes.push(SchemaChange::CollectionRenamed { from: remote.name.clone(), to: local.name.clone() });
+    }
+    if local.collection_type != remote.collection_type {
+        changes.push(SchemaChange::TypeChanged { collection: collection.clone(), before: remote.collection_type, after: local.collection_type });
+    }
+    if local.view_query != remote.view_query {
+        changes.push(SchemaChange::ViewQueryChanged { collection: collection.clone(), before: remote.view_query.clone(), after: local.view_query.clone() });
+    }
+    for rule in RuleKind::ALL {
+        let (before, after) = (rule.of(remote), rule.of(local));
+        if before != after {
+            changes.push(SchemaChange::RuleChanged { collection: collection.clone(), rule, before, after });
+        }
+    }
+    // The rules of the options are already compared above.
+    let mut keys = remote.options.keys().chain(local.options.keys().filter(|x| !remote.options.contains_key(*x)))
+        .filter(|x| *x != RuleKind::Auth.key() && *x != RuleKind::Manage.key())
+        .collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+        let (before, after) = (remote.options.get(key), local.options.get(key));
+        if before != after {
+            changes.push(SchemaChange::OptionChanged { collection: collection.clone(), key: key.clone(), before: before.cloned(), after: after.cloned() });
+        }
+    }
+    let mut matched_remote = vec![false; remote.fields.len()];
+    for field in &local.fields {
+        match find_match(&remote.fields, &matched_remote, |x| x.id(), |x| x.name(), field.id(), field.name()) {
+            Some(index) => {
+                matched_remote[index] = true;
+                let before = &remote.fields[index];
+                if field_options(field) != field_options(before) {
+                    changes.push(SchemaChange::FieldChanged { collection: collection.clone(), before: before.clone(), after: field.clone() });
+                }
+            }
+            None => changes.push(SchemaChange::FieldAdded { collection: collection.clone(), field: field.clone() }),
+        }
+    }
+    for (field, _) in remote.fields.iter().zip(matched_remote).filter(|(_, matched)| !matched) {
+        changes.push(SchemaChange::FieldRemoved { collection: collection.clone(), field: field.clone() });
+    }
+    for index in local.indexes.iter().filter(|x| !remote.indexes.contains(x)) {
+        changes.push(SchemaChange::IndexAdded { collection: collection.clone(), index: index.clone() });
+    }
+    for index in remote.indexes.iter().filter(|x| !local.indexes.contains(x)) {
+        changes.push(SchemaChange::IndexRemoved { collection: collection.clone(), index: index.clone() });
+    }
+}
+
+/// The JSON properties of a field, without its ID,
+/// which may be missing from the local definition.
+fn field_options(field: &Field) -> serde_json::Map<String, Value> {
+    let mut options = match serde_json::to_value(field) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    options.remove("id");
+    options
+}
+
+fn fmt_type(collection_type: &CollectionType) -> &'static str {
+    match collection_type {
+        CollectionType::Base => "base",
+        CollectionType::Auth => "auth",
+        CollectionType::View => "view",
+    }
+}
+
+fn fmt_rule(rule: &Option<String>) -> String {
+    match rule {
+        Some(rule) => format!("{:?}", rule),
+        None => "null".to_string(),
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::CollectionAdded { name } => write!(f, "+ collection {}", name),
+            SchemaChange::CollectionRemoved { name } => write!(f, "- collection {}", name),
+            SchemaChange::CollectionRenamed { from, to } => write!(f, "~ collection {} renamed to {}", from, to),
+            SchemaChange::TypeChanged { collection, before, after } => {
+                write!(f, "~ {}.type: {} -> {}", collection, fmt_type(before), fmt_type(after))
+            }
+            SchemaChange::ViewQueryChanged { collection, before, after } => {
+                write!(f, "~ {}.viewQuery: {} -> {}", collection, fmt_rule(before), fmt_rule(after))
+            }
+            SchemaChange::OptionChanged { collection, key, before, after } => {
+                let null = Value::Null;
+                write!(f, "~ {}.{}: {} -> {}", collection, key, before.as_ref().unwrap_or(&null), after.as_ref().unwrap_or(&null))
+            }
+            SchemaChange::RuleChanged { collection, rule, before, after } => {
+                write!(f, "~ {}.{}: {} -> {}", collection, rule.key(), fmt_rule(before), fmt_rule(after))
+            }
+            SchemaChange::FieldAdded { collection, field } => write!(f, "+ field {}.{} ({})", collection, field.name(), field.type_name()),
+            SchemaChange::FieldRemoved { collection, field } => write!(f, "- field {}.{} ({})", collection, field.name(), field.type_name()),
+            SchemaChange::FieldChanged { collection, before, after } => {
+                write!(f, "~ field {}.{} ({})", collection, after.name(), after.type_name())?;
+                let (before, after) = (field_options(before), field_options(after));
+                let mut keys = before.keys().chain(after.keys().filter(|x| !before.contains_key(*x))).collect::<Vec<_>>();
+                keys.sort();
+                let null = Value::Null;
+                let mut separator = ":";
+                for key in keys {
+                    let (old, new) = (before.get(key).unwrap_or(&null), after.get(key).unwrap_or(&null));
+                    if old != new {
+                        write!(f, "{} {} {} -> {}", separator, key, old, new)?;
+                        separator = ",";
+                    }
+                }
+                Ok(())
+            }
+            SchemaChange::IndexAdded { collection, index } => write!(f, "+ index on {}: {}", collection, index),
+            SchemaChange::IndexRemoved { collection, index } => write!(f, "- index on {}: {}", collection, index),
+        }
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "No changes.");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
+pub mod batch_service;
 pub mod collection_service;
-pub mod record_service;
\ No newline at end of file
+pub mod file_service;
+pub mod record_service;
+pub mod realtime_service;
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use urlencoding::encode;
+use crate::common::{status_code, ViewOptions};
+use crate::error::ApiError;
+use crate::pocketbase::PocketBaseRef;
+use crate::services::record_service::refreshed_auth_headers;
+
+/// A request queued in a [BatchService].
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BatchRequest {
+    method: &'static str,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct BatchPayload<'a> {
+    requests: &'a [BatchRequest],
+}
+
+/// The error returned by the server when a batch fails.
+#[derive(Deserialize)]
+struct BatchError {
+    status: u16,
+    message: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+/// The result of one of the requests of a batch, in the same order as they were queued.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchResult {
+    /// The HTTP status of the request.
+    pub status: u16,
+    /// The response of the request, usually a record, or null for a deletion.
+    #[serde(default)]
+    pub body: serde_json::Value,
+}
+
+impl BatchResult {
+    /// Deserializes the body of the response, usually into a record.
+    pub fn record<E: DeserializeOwned>(&self) -> Result<E, ApiError> {
+        Ok(serde_json::from_value(self.body.clone())?)
+    }
+}
+
+/// Queues record operations, possibly on several collections,
+/// and sends them in a single transaction: either all of them succeed, or none of them do.
+///
+/// The batch API must be enabled in the settings of the server.
+///
+/// ```rust,no_run
+/// # use pbrsdk::*;
+/// # async fn run(pb: PocketBase<DefaultAuthRecord>) -> Result<(), ApiError> {
+/// let mut batch = pb.create_batch();
+/// batch.collection("articles").create(serde_json::json!({ "name": "Chair" }), None)?;
+/// batch.collection("articles").delete("abc");
+/// let results = batch.send().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BatchService<T>
+where T: DeserializeOwned + Clone {
+    pub(crate) pb: Arc<PocketBaseRef<T>>,
+    pub(crate) requests: Vec<BatchRequest>,
+}
+
+/// Queues requests concerning the records of a collection into a [BatchService].
+pub struct SubBatchService<'a, T>
+where T: DeserializeOwned + Clone {
+    batch: &'a mut BatchService<T>,
+    collection_id_or_name: String,
+}
+
+impl<T> BatchService<T>
+where T: DeserializeOwned + Clone {
+    /// Queues requests concerning the records of the given collection.
+    pub fn collection(&mut self, name_or_id: impl Into<String>) -> SubBatchService<'_, T> {
+        SubBatchService {
+            batch: self,
+            collection_id_or_name: name_or_id.into(),
+        }
+    }
+
+    /// The number of queued requests.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Whether no request has been queued yet.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends all the queued requests in a single transaction.
+    /// If any of them fails, none of them is applied and [ApiError::Batch] is returned,
+    /// describing the requests that failed.
+    pub async fn send(self) -> Result<Vec<BatchResult>, ApiError> {
+        let url = format!("{}/api/batch", self.pb.base_url);
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .json(&BatchPayload { requests: &self.requests })
+            .send().await?
+            .text().await?;
+        match serde_json::from_str::<Vec<BatchResult>>(&body) {
+            Ok(results) => Ok(results),
+            Err(err) => match serde_json::from_str::<BatchError>(&body) {
+                Ok(error) => Err(ApiError::Batch {
+                    status: status_code(error.status),
+                    message: error.message,
+                    data: error.data,
+                }),
+                Err(_) => Err(ApiError::Json(err)),
+            },
+        }
+    }
+}
+
+impl<T> SubBatchService<'_, T>
+where T: DeserializeOwned + Clone {
+    fn push(&mut self, method: &'static str, url: String, body: Option<serde_json::Value>) {
+        self.batch.requests.push(BatchRequest { method, url, body });
+    }
+
+    fn records_path(&self) -> String {
+        format!("/api/collections/{}/records", self.collection_id_or_name)
+    }
+
+    /// Queues the creation of a record.
+    pub fn create<S: Serialize>(&mut self, body: S, options: Option<ViewOptions>) -> Result<(), ApiError> {
+        let url = format!("{}{}", self.records_path(), options.unwrap_or_default().to_url_query());
+        self.push("POST", url, Some(serde_json::to_value(body)?));
+        Ok(())
+    }
+
+    /// Queues the update of an existing record by its ID.
+    pub fn update<S: Serialize>(&mut self, id: impl Into<String>, body: S, options: Option<ViewOptions>) -> Result<(), ApiError> {
+        let url = format!("{}/{}{}", self.records_path(), encode(&id.into()), options.unwrap_or_default().to_url_query());
+        self.push("PATCH", url, Some(serde_json::to_value(body)?));
+        Ok(())
+    }
+
+    /// Queues the creation of a record, or its update if the body contains
+    /// the `id` of an existing record.
+    pub fn upsert<S: Serialize>(&mut self, body: S, options: Option<ViewOptions>) -> Result<(), ApiError> {
+        let url = format!("{}{}", self.records_path(), options.unwrap_or_default().to_url_query());
+        self.push("PUT", url, Some(serde_json::to_value(body)?));
+        Ok(())
+    }
+
+    /// Queues the deletion of a record by its ID.
+    pub fn delete(&mut self, id: impl Into<String>) {
+        let url = format!("{}/{}", self.records_path(), encode(&id.into()));
+        self.push("DELETE", url, None);
+    }
+}
@@ -1,10 +1,126 @@
+use std::path::Path;
 use std::sync::Arc;
+use reqwest::StatusCode;
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use urlencoding::encode;
 use crate::error::ApiError;
+use crate::common::{handle_empty_response_body, handle_response_body, FullListOptions, ListOptions, ViewOptions};
+use crate::datetime::PbDateTime;
+use crate::fields::Field;
 use crate::pocketbase::PocketBaseRef;
+use crate::services::record_service::{fetch_full_list, refreshed_auth_headers, ListResponse};
+
+/// The type of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionType {
+    /// A regular collection.
+    Base,
+    /// A collection whose records can authenticate.
+    Auth,
+    /// A read-only collection whose records are the result of an SQL query.
+    View,
+}
+
+/// The definition of a collection, as returned by the `/api/collections` routes
+/// and as found in the JSON export of the collections (`pb_schema.json`).
+///
+/// # Note
+///
+/// An API rule set to [Option::None] means that only superusers can perform the action,
+/// whereas an empty string means that everyone can.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionModel {
+    /// The ID of the collection. Leave it empty to let the server generate it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// The unique name of the collection.
+    pub name: String,
+    /// The type of the collection.
+    #[serde(rename = "type")]
+    pub collection_type: CollectionType,
+    /// Whether the collection is a system collection, which cannot be deleted or renamed.
+    #[serde(default)]
+    pub system: bool,
+    /// The API rule to list the records.
+    #[serde(default)]
+    pub list_rule: Option<String>,
+    /// The API rule to view a record.
+    #[serde(default)]
+    pub view_rule: Option<String>,
+    /// The API rule to create a record.
+    #[serde(default)]
+    pub create_rule: Option<String>,
+    /// The API rule to update a record.
+    #[serde(default)]
+    pub update_rule: Option<String>,
+    /// The API rule to delete a record.
+    #[serde(default)]
+    pub delete_rule: Option<String>,
+    /// The fields of the collection.
+    #[serde(default)]
+    pub fields: Vec<Field>,
+    /// The SQL statements creating the indexes of the collection.
+    #[serde(default)]
+    pub indexes: Vec<String>,
+    /// The SQL query of a `view` collection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_query: Option<String>,
+    /// The creation date of the collection.
+    #[serde(default, with = "crate::datetime::optional", skip_serializing_if = "Option::is_none")]
+    pub created: Option<PbDateTime>,
+    /// The date of the last update of the collection.
+    #[serde(default, with = "crate::datetime::optional", skip_serializing_if = "Option::is_none")]
+    pub updated: Option<PbDateTime>,
+    /// The remaining options, which depend on the type of the collection.
+    /// For `auth` collections, it contains `authRule`, `manageRule`, `passwordAuth`,
+    /// `oauth2`, `mfa`, `otp`, the durations of the tokens and the email templates.
+    #[serde(flatten)]
+    pub options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl CollectionModel {
+    /// Creates the model of a new collection, without any field.
+    pub fn new(name: impl Into<String>, collection_type: CollectionType) -> Self {
+        CollectionModel {
+            id: String::new(),
+            name: name.into(),
+            collection_type,
+            system: false,
+            list_rule: None,
+            view_rule: None,
+            create_rule: None,
+            update_rule: None,
+            delete_rule: None,
+            fields: Vec::new(),
+            indexes: Vec::new(),
+            view_query: None,
+            created: None,
+            updated: None,
+            options: serde_json::Map::new(),
+        }
+    }
+
+    /// Reads a JSON export of the collections, such as `pb_schema.json`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Vec<CollectionModel>, ApiError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportPayload<'a, S: Serialize> {
+    collections: &'a [S],
+    delete_missing: bool,
+}
 
 /// Handles requests meant to concern the collections themselves,
 /// rather than the records they contain.
+///
+/// All of these routes require to be authenticated as a superuser.
 pub struct CollectionService<T>
 where T: DeserializeOwned + Clone {
     pub(crate) base_crud_path: &'static str,
@@ -19,11 +135,131 @@ where T: DeserializeOwned + Clone {
         self.base_crud_path
     }
 
+    /// Fetches pages of collections.
+    pub async fn get_list(&self, options: ListOptions) -> Result<ListResponse<CollectionModel>, ApiError> {
+        self.get_list_with_query(options, &[]).await
+    }
+
+    /// Fetches pages of collections, with additional query parameters.
+    async fn get_list_with_query(&self, options: ListOptions, query: &[(String, String)]) -> Result<ListResponse<CollectionModel>, ApiError> {
+        let url = format!("{}{}{}", self.pb.base_url, self.base_crud_path, options.to_url_query_with(query));
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .get(&url)
+            .headers(headers)
+            .send().await?
+            .text().await?;
+        handle_response_body(&body)
+    }
+
     /// Gets the full list of collections.
-    pub async fn get_full_list(&self) -> Result<String, ApiError> {
-        // TODO: requires authentication header
+    pub async fn get_full_list(&self) -> Result<Vec<CollectionModel>, ApiError> {
+        self.get_full_list_with(FullListOptions::default()).await
+    }
+
+    /// Same as [CollectionService::get_full_list], with options applied to every page request.
+    pub async fn get_full_list_with(&self, options: FullListOptions) -> Result<Vec<CollectionModel>, ApiError> {
+        let concurrency = options.concurrency;
+        let (options, query) = options.into_parts();
+        fetch_full_list(options, concurrency, |options| self.get_list_with_query(options, &query)).await
+    }
+
+    /// Fetches one collection based on its ID or name, which must exist.
+    /// If the collection isn't found, the server will return a 404 error.
+    pub async fn get_one(&self, id_or_name: impl Into<String>, options: Option<ViewOptions>) -> Result<CollectionModel, ApiError> {
+        let url = format!("{}{}/{}{}", self.pb.base_url, self.base_crud_path, encode(&id_or_name.into()), options.unwrap_or_default().to_url_query());
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .get(&url)
+            .headers(headers)
+            .send().await?
+            .text().await?;
+        handle_response_body(&body)
+    }
+
+    /// Returns the first collection matching the filter.
+    /// For consistency with `get_one()`, this method will throw a 404 if the collection isn't found.
+    pub async fn get_first_list_item(&self, filter: impl Into<String>, options: Option<ViewOptions>) -> Result<CollectionModel, ApiError> {
+        let list_options = ListOptions::from_view(Some(1), Some(1), Some(filter.into()), options);
+        let mut page = self.get_list(list_options).await?;
+        page.items.pop().ok_or_else(|| ApiError::Http(StatusCode::NOT_FOUND, "There is no collection matching the filter.".to_string()))
+    }
+
+    /// Creates a new collection and returns it.
+    /// The body is usually a [CollectionModel].
+    pub async fn create<S: Serialize>(&self, body: S) -> Result<CollectionModel, ApiError> {
         let url = format!("{}{}", self.pb.base_url, self.base_crud_path);
-        let body = self.pb.client.get(url).send().await?.text().await?;
-        Ok(body)
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .json(&body)
+            .send().await?
+            .text().await?;
+        handle_response_body(&body)
+    }
+
+    /// Updates an existing collection by its ID or name, and returns it.
+    /// The body can be a [CollectionModel] or any partial representation of it.
+    pub async fn update<S: Serialize>(&self, id_or_name: impl Into<String>, body: S) -> Result<CollectionModel, ApiError> {
+        let url = format!("{}{}/{}", self.pb.base_url, self.base_crud_path, encode(&id_or_name.into()));
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .patch(&url)
+            .headers(headers)
+            .json(&body)
+            .send().await?
+            .text().await?;
+        handle_response_body(&body)
+    }
+
+    /// Deletes a collection, along with all its records, by its ID or name.
+    pub async fn delete(&self, id_or_name: impl Into<String>) -> Result<(), ApiError> {
+        let url = format!("{}{}/{}", self.pb.base_url, self.base_crud_path, encode(&id_or_name.into()));
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .delete(&url)
+            .headers(headers)
+            .send().await?
+            .text().await?;
+        handle_empty_response_body(&body)
+    }
+
+    /// Deletes all the records of a collection, but keeps the collection itself.
+    pub async fn truncate(&self, id_or_name: impl Into<String>) -> Result<(), ApiError> {
+        let url = format!("{}{}/{}/truncate", self.pb.base_url, self.base_crud_path, encode(&id_or_name.into()));
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .delete(&url)
+            .headers(headers)
+            .send().await?
+            .text().await?;
+        handle_empty_response_body(&body)
+    }
+
+    /// Imports the given collections, creating the missing ones and updating the existing ones.
+    /// The collections are matched by their ID, so the exported IDs must be kept.
+    ///
+    /// If `delete_missing` is true, the collections (and their records)
+    /// and the fields that aren't part of the import are deleted.
+    ///
+    /// The collections are usually [CollectionModel], but any serializable representation works.
+    pub async fn import<S: Serialize>(&self, collections: &[S], delete_missing: bool) -> Result<(), ApiError> {
+        let url = format!("{}{}/import", self.pb.base_url, self.base_crud_path);
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .put(&url)
+            .headers(headers)
+            .json(&ImportPayload { collections, delete_missing })
+            .send().await?
+            .text().await?;
+        handle_empty_response_body(&body)
+    }
+
+    /// Imports the collections of a JSON export, such as `pb_schema.json`.
+    /// See [CollectionService::import].
+    pub async fn import_file(&self, path: impl AsRef<Path>, delete_missing: bool) -> Result<(), ApiError> {
+        let collections = CollectionModel::from_file(path)?;
+        self.import(&collections, delete_missing).await
     }
 }
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use urlencoding::encode;
+use crate::common::{handle_response_body, BaseSystemFields, ResponseError};
+use crate::error::ApiError;
+use crate::pocketbase::PocketBaseRef;
+use crate::services::record_service::refreshed_auth_headers;
+
+/// The query parameters of a file URL.
+#[derive(Debug, Default, Clone)]
+pub struct FileOptions {
+    /// The size of the thumbnail of an image, such as `100x100`, `0x300` or `100x100f`.
+    /// It must be one of the sizes allowed by the field.
+    pub thumb: Option<String>,
+    /// Whether the server should force the browser to download the file,
+    /// rather than displaying it.
+    pub download: bool,
+    /// The token required to access a protected file, see [FileService::get_token].
+    pub token: Option<String>,
+}
+
+impl FileOptions {
+    pub(crate) fn to_url_query(&self) -> String {
+        let mut url = "?".to_string();
+        if let Some(thumb) = &self.thumb { url.push_str(&format!("thumb={}&", encode(thumb).into_owned())); }
+        if self.download { url.push_str("download=1&"); }
+        if let Some(token) = &self.token { url.push_str(&format!("token={}&", encode(token).into_owned())); }
+        if url.len() == 1 {
+            return String::new();
+        }
+        url.strip_suffix("&").unwrap().to_string()
+    }
+}
+
+#[derive(Deserialize)]
+struct FileTokenResponse {
+    token: String,
+}
+
+/// Handles the files stored in the `file` fields of the records.
+pub struct FileService<T>
+where T: DeserializeOwned + Clone {
+    pub(crate) pb: Arc<PocketBaseRef<T>>,
+}
+
+impl<T> FileService<T>
+where T: DeserializeOwned + Clone {
+    /// Builds the absolute URL of a file of a record.
+    /// Returns an empty string if the file name or the ID of the record is empty.
+    pub fn get_url(&self, record: &impl BaseSystemFields, filename: impl Into<String>, options: Option<FileOptions>) -> String {
+        let filename = filename.into();
+        let collection = if record.collection_id().is_empty() { record.collection_name() } else { record.collection_id() };
+        if filename.is_empty() || record.id().is_empty() || collection.is_empty() {
+            return String::new();
+        }
+        format!(
+            "{}/api/files/{}/{}/{}{}",
+            self.pb.base_url,
+            encode(collection),
+            encode(record.id()),
+            encode(&filename),
+            options.unwrap_or_default().to_url_query(),
+        )
+    }
+
+    /// Requests a short-lived token to access the protected files,
+    /// which requires to be authenticated.
+    /// The token must be given to [FileService::get_url] through the options.
+    pub async fn get_token(&self) -> Result<String, ApiError> {
+        let url = format!("{}/api/files/token", self.pb.base_url);
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .send().await?
+            .text().await?;
+        let response: FileTokenResponse = handle_response_body(&body)?;
+        Ok(response.token)
+    }
+
+    /// Downloads a file of a record, as a stream of bytes,
+    /// so that big files don't have to be loaded in memory.
+    ///
+    /// The request fails if the file doesn't exist, or if it's protected
+    /// and no valid token was given through the options.
+    pub async fn download(&self, record: &impl BaseSystemFields, filename: impl Into<String>, options: Option<FileOptions>) -> Result<BoxStream<'static, Result<Bytes, ApiError>>, ApiError> {
+        let url = self.get_url(record, filename, options);
+        if url.is_empty() {
+            return Err(ApiError::Http(reqwest::StatusCode::NOT_FOUND, "The record or the file name is missing.".to_string()));
+        }
+        let headers = refreshed_auth_headers(&self.pb).await;
+        let response = self.pb.client
+            .get(&url)
+            .headers(headers)
+            .send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            let message = serde_json::from_str::<ResponseError>(&body).map(|x| x.message).unwrap_or(body);
+            return Err(ApiError::Http(status, message));
+        }
+        Ok(response.bytes_stream().map_err(ApiError::from).boxed())
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use crate::common::handle_empty_response_body;
+use crate::error::ApiError;
+use crate::pocketbase::PocketBaseRef;
+use crate::services::record_service::refreshed_auth_headers;
+
+/// The delays (in milliseconds) to wait before each reconnection attempt.
+/// Once they have all been tried, the last one is reused indefinitely.
+const RECONNECT_INTERVALS: [u64; 7] = [200, 300, 500, 1000, 1200, 1500, 2000];
+
+/// A callback receiving the raw JSON data of an event.
+type Listener = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// The kind of change that triggered a realtime event on a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubscriptionAction {
+    /// A record was created.
+    Create,
+    /// A record was updated.
+    Update,
+    /// A record was deleted.
+    Delete,
+}
+
+/// The message sent by the server when a subscribed record changes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordSubscription<E> {
+    /// What happened to the record.
+    pub action: SubscriptionAction,
+    /// The record itself, as it is after the change
+    /// (or as it was before being deleted).
+    pub record: E,
+}
+
+/// The state of the realtime connection, shared by all the services of a [PocketBase](crate::PocketBase) instance.
+#[derive(Default)]
+pub(crate) struct RealtimeClient {
+    state: Mutex<RealtimeState>,
+    /// Prevents two subscriptions from opening two connections at the same time.
+    connecting: tokio::sync::Mutex<()>,
+}
+
+#[derive(Default)]
+struct RealtimeState {
+    client_id: Option<String>,
+    subscriptions: HashMap<String, Vec<(u64, Listener)>>,
+    next_listener_id: u64,
+    task: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SseEvent {
+    pub(crate) id: String,
+    pub(crate) event: String,
+    pub(crate) data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectMessage {
+    client_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionsPayload {
+    client_id: String,
+    subscriptions: Vec<String>,
+}
+
+impl RealtimeClient {
+    fn is_connected(&self) -> bool {
+        self.state.lock().unwrap().client_id.is_some()
+    }
+
+    /// Checks if the background task is still alive,
+    /// meaning that it's either listening or trying to reconnect.
+    fn is_running(&self) -> bool {
+        self.state.lock().unwrap().task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    fn has_subscriptions(&self) -> bool {
+        !self.state.lock().unwrap().subscriptions.is_empty()
+    }
+
+    fn set_client_id(&self, client_id: Option<String>) {
+        self.state.lock().unwrap().client_id = client_id;
+    }
+
+    fn remove_listener(&self, topic: &str, listener_id: u64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(listeners) = state.subscriptions.get_mut(topic) {
+            listeners.retain(|(id, _)| *id != listener_id);
+            if listeners.is_empty() {
+                state.subscriptions.remove(topic);
+            }
+        }
+    }
+
+    fn disconnect(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(task) = state.task.take() {
+            task.abort();
+        }
+        state.client_id = None;
+    }
+
+    fn dispatch(&self, events: Vec<SseEvent>) {
+        for event in events {
+            // The listeners are cloned so that the lock
+            // isn't held while the callbacks are running.
+            let listeners = self.state.lock().unwrap().subscriptions.get(&event.event)
+                .map(|listeners| listeners.iter().map(|(_, listener)| listener.clone()).collect::<Vec<Listener>>())
+                .unwrap_or_default();
+            for listener in listeners {
+                listener(&event.data);
+            }
+        }
+    }
+}
+
+/// Handles the realtime connection with the PocketBase server,
+/// through the Server-Sent Events of the `/api/realtime` route.
+///
+/// The connection is opened on the first subscription and closed
+/// once there is no subscription left. If the connection is lost,
+/// it is automatically reestablished and the subscriptions are submitted again.
+pub struct RealtimeService<T>
+where T: DeserializeOwned + Clone {
+    pub(crate) pb: Arc<PocketBaseRef<T>>,
+}
+
+impl<T> RealtimeService<T>
+where T: DeserializeOwned + Clone + Send + Sync + 'static {
+    /// The ID given by the server to the current connection.
+    /// It's [Option::None] if the client isn't connected.
+    pub fn client_id(&self) -> Option<String> {
+        self.pb.realtime.state.lock().unwrap().client_id.clone()
+    }
+
+    /// Checks if the client is currently connected to the server.
+    pub fn is_connected(&self) -> bool {
+        self.pb.realtime.is_connected()
+    }
+
+    /// Subscribes to a topic, which is usually a collection's name
+    /// followed by either `/*` or `/RECORD_ID`.
+    /// The callback receives the data of each message, deserialized as `E`.
+    ///
+    /// Connects to the server if it's not already the case.
+    pub async fn subscribe<E, F>(&self, topic: impl Into<String>, callback: F) -> Result<(), ApiError>
+    where E: DeserializeOwned + 'static, F: Fn(E) + Send + Sync + 'static {
+        let topic = topic.into();
+        let listener: Listener = Arc::new(move |data: &str| {
+            if let Ok(message) = serde_json::from_str::<E>(data) {
+                callback(message);
+            }
+        });
+        let (listener_id, is_new_topic) = {
+            let mut state = self.pb.realtime.state.lock().unwrap();
+            let listener_id = state.next_listener_id;
+            state.next_listener_id += 1;
+            let listeners = state.subscriptions.entry(topic.clone()).or_default();
+            listeners.push((listener_id, listener));
+            (listener_id, listeners.len() == 1)
+        };
+        let result = if self.pb.realtime.is_connected() {
+            if is_new_topic { submit_subscriptions(&self.pb).await } else { Ok(()) }
+        } else if self.pb.realtime.is_running() {
+            // The subscriptions will be submitted as soon as the connection is reestablished.
+            Ok(())
+        } else {
+            connect(&self.pb).await
+        };
+        if result.is_err() {
+            self.pb.realtime.remove_listener(&topic, listener_id);
+        }
+        result
+    }
+
+    /// Removes all the listeners of the given topic.
+    /// The connection is closed if there is no subscription left.
+    pub async fn unsubscribe(&self, topic: impl Into<String>) -> Result<(), ApiError> {
+        let topic = topic.into();
+        let removed = self.pb.realtime.state.lock().unwrap().subscriptions.remove(&topic).is_some();
+        if removed { self.on_subscriptions_changed().await } else { Ok(()) }
+    }
+
+    /// Removes all the listeners of the topics starting with the given prefix.
+    /// The connection is closed if there is no subscription left.
+    pub async fn unsubscribe_by_prefix(&self, prefix: impl Into<String>) -> Result<(), ApiError> {
+        let prefix = prefix.into();
+        let removed = {
+            let mut state = self.pb.realtime.state.lock().unwrap();
+            let count = state.subscriptions.len();
+            state.subscriptions.retain(|topic, _| !topic.starts_with(&prefix));
+            count != state.subscriptions.len()
+        };
+        if removed { self.on_subscriptions_changed().await } else { Ok(()) }
+    }
+
+    /// Removes all the subscriptions and closes the connection.
+    pub fn unsubscribe_all(&self) {
+        self.pb.realtime.state.lock().unwrap().subscriptions.clear();
+        self.pb.realtime.disconnect();
+    }
+
+    async fn on_subscriptions_changed(&self) -> Result<(), ApiError> {
+        if !self.pb.realtime.has_subscriptions() {
+            self.pb.realtime.disconnect();
+            Ok(())
+        } else if self.pb.realtime.is_connected() {
+            submit_subscriptions(&self.pb).await
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Opens the connection, submits the current subscriptions,
+/// and spawns the task listening to the incoming messages.
+async fn connect<T>(pb: &Arc<PocketBaseRef<T>>) -> Result<(), ApiError>
+where T: DeserializeOwned + Clone + Send + Sync + 'static {
+    let _guard = pb.realtime.connecting.lock().await;
+    if pb.realtime.is_connected() {
+        // Another subscription opened the connection in the meantime.
+        return submit_subscriptions(pb).await;
+    }
+    let (response, buffer) = open_stream(pb).await?;
+    if let Err(err) = submit_subscriptions(pb).await {
+        pb.realtime.set_client_id(None);
+        return Err(err);
+    }
+    let task = tokio::spawn(listen(Arc::downgrade(pb), response, buffer));
+    let mut state = pb.realtime.state.lock().unwrap();
+    if let Some(previous_task) = state.task.replace(task) {
+        previous_task.abort();
+    }
+    Ok(())
+}
+
+/// Sends the request to `/api/realtime` and waits for the `PB_CONNECT` event,
+/// from which the client ID is extracted.
+/// Returns the response, to keep reading from it, along with what remains of the buffer.
+async fn open_stream<T>(pb: &PocketBaseRef<T>) -> Result<(Response, Vec<u8>), ApiError>
+where T: DeserializeOwned + Clone {
+    let url = format!("{}/api/realtime", pb.base_url);
+    let mut response = pb.client
+        .get(&url)
+        .header("Accept", "text/event-stream")
+        .send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await?;
+        handle_empty_response_body(&body)?;
+        return Err(ApiError::Http(status, body));
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend(chunk.iter().filter(|byte| **byte != b'\r'));
+        for event in drain_sse_events(&mut buffer) {
+            if event.event == "PB_CONNECT" {
+                let client_id = serde_json::from_str::<ConnectMessage>(&event.data)
+                    .map(|message| message.client_id)
+                    .unwrap_or(event.id);
+                pb.realtime.set_client_id(Some(client_id));
+                return Ok((response, buffer));
+            }
+        }
+    }
+    Err(ApiError::Realtime("the server closed the connection before sending PB_CONNECT".to_string()))
+}
+
+/// Sends the complete list of topics to the server.
+async fn submit_subscriptions<T>(pb: &Arc<PocketBaseRef<T>>) -> Result<(), ApiError>
+where T: DeserializeOwned + Clone {
+    let payload = {
+        let state = pb.realtime.state.lock().unwrap();
+        SubscriptionsPayload {
+            client_id: state.client_id.clone().unwrap_or_default(),
+            subscriptions: state.subscriptions.keys().cloned().collect(),
+        }
+    };
+    let url = format!("{}/api/realtime", pb.base_url);
+    let headers = refreshed_auth_headers(pb).await;
+    let body = pb.client
+        .post(&url)
+        .headers(headers)
+        .json(&payload)
+        .send().await?
+        .text().await?;
+    handle_empty_response_body(&body)
+}
+
+/// Reads the messages until the connection is lost, then reconnects
+/// and submits the subscriptions again. The task ends when the [PocketBase](crate::PocketBase)
+/// instance is dropped or when there is no subscription left.
+async fn listen<T>(pb: Weak<PocketBaseRef<T>>, mut response: Response, mut buffer: Vec<u8>)
+where T: DeserializeOwned + Clone + Send + Sync + 'static {
+    loop {
+        while let Ok(Some(chunk)) = response.chunk().await {
+            buffer.extend(chunk.iter().filter(|byte| **byte != b'\r'));
+            let events = drain_sse_events(&mut buffer);
+            let Some(pb) = pb.upgrade() else { return };
+            pb.realtime.dispatch(events);
+        }
+        match pb.upgrade() {
+            Some(pb) => pb.realtime.set_client_id(None),
+            None => return,
+        }
+        let mut attempt = 0usize;
+        loop {
+            let delay = RECONNECT_INTERVALS[attempt.min(RECONNECT_INTERVALS.len() - 1)];
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            attempt += 1;
+            let Some(pb) = pb.upgrade() else { return };
+            if !pb.realtime.has_subscriptions() {
+                pb.realtime.set_client_id(None);
+                return;
+            }
+            let _guard = pb.realtime.connecting.lock().await;
+            if let Ok((new_response, new_buffer)) = open_stream(&pb).await {
+                if submit_subscriptions(&pb).await.is_ok() {
+                    response = new_response;
+                    buffer = new_buffer;
+                    break;
+                }
+                pb.realtime.set_client_id(None);
+            }
+        }
+    }
+}
+
+/// Removes all the complete events from the buffer and parses them.
+/// An incomplete event is left in the buffer until the rest of it is received.
+/// The buffer is expected not to contain any carriage return.
+pub(crate) fn drain_sse_events(buffer: &mut Vec<u8>) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.windows(2).position(|window| window == b"\n\n") {
+        let raw: Vec<u8> = buffer.drain(..end + 2).collect();
+        let raw = String::from_utf8_lossy(&raw[..end]);
+        let event = parse_sse_event(&raw);
+        if !event.event.is_empty() || !event.data.is_empty() {
+            events.push(event);
+        }
+    }
+    events
+}
+
+fn parse_sse_event(raw: &str) -> SseEvent {
+    let mut event = SseEvent::default();
+    let mut data_lines: Vec<&str> = Vec::new();
+    for line in raw.lines() {
+        let (field, value) = match line.find(':') {
+            Some(idx) => (&line[..idx], &line[idx + 1..]),
+            None => (line, ""),
+        };
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "id" => event.id = value.to_string(),
+            "event" => event.event = value.to_string(),
+            "data" => data_lines.push(value),
+            _ => {} // comments and unknown fields are ignored
+        }
+    }
+    event.data = data_lines.join("\n");
+    event
+}
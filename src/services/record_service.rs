@@ -1,13 +1,19 @@
+use std::future::Future;
 use std::sync::{Arc};
+use std::time::Duration;
 use reqwest::{StatusCode};
 use reqwest::header::HeaderMap;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 use urlencoding::encode;
 use crate::error::{ApiError};
-use crate::auth::{AuthRequestPayload, AuthResponse, DefaultAuthResponseRecord};
-use crate::common::{ResponseError, ViewOptions, ListOptions};
-use crate::pocketbase::PocketBaseRef;
+use crate::auth::{AuthMethodsList, AuthRequestPayload, MfaChallenge, AuthResponse, OAuth2CodePayload, OtpAuthPayload, OtpResponse, EmailPayload, TokenPayload, ConfirmPasswordResetPayload, EmailChangePayload, ConfirmEmailChangePayload, ImpersonatePayload};
+use crate::common::{handle_empty_response_body, handle_response_body, AuthOptions, FullListOptions, ViewOptions, ListOptions};
+use crate::pocketbase::{PocketBase, PocketBaseRef};
+use crate::backends::InMemoryBackend;
+use crate::uploads::RecordFiles;
+use crate::services::realtime_service::{RealtimeService, RecordSubscription};
 
 /// The server's response when requesting a list of records.
 #[derive(Debug, Deserialize)]
@@ -32,7 +38,60 @@ pub struct ListResponse<T> {
     pub total_pages: i64,
 }
 
+impl<T> ListResponse<T> {
+    /// The page that follows this one, if this one is full.
+    /// The server's `per_page` is used, because it caps the one that was asked.
+    pub(crate) fn next_page(&self) -> Option<u64> {
+        if !self.items.is_empty() && self.items.len() as u64 >= self.per_page {
+            Some(self.page + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The number of records fetched per request when walking through a whole collection.
+const DEFAULT_BATCH_SIZE: u64 = 1000;
+
+/// Fetches all the pages of a list, `fetch_page` being the request of a single page.
+///
+/// The pages are fetched one after the other, unless `concurrency` is greater than 1.
+/// In that case, the first page gives the total number of pages,
+/// then the remaining ones are fetched with at most `concurrency` requests in flight.
+pub(crate) async fn fetch_full_list<E, F, Fut>(options: ListOptions, concurrency: Option<usize>, fetch_page: F) -> Result<Vec<E>, ApiError>
+where F: Fn(ListOptions) -> Fut, Fut: Future<Output = Result<ListResponse<E>, ApiError>> {
+    let per_page = options.per_page.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let options = ListOptions { per_page: Some(per_page), ..options };
+    match concurrency {
+        Some(concurrency) if concurrency > 1 => {
+            let first_page = fetch_page(ListOptions { page: Some(1), skip_total: Some(false), ..options.clone() }).await?;
+            let total_pages = first_page.total_pages.max(1) as u64;
+            let mut items = first_page.items;
+            let options = ListOptions { skip_total: Some(true), ..options };
+            let pages = stream::iter(2..=total_pages)
+                .map(|page| fetch_page(ListOptions { page: Some(page), ..options.clone() }))
+                .buffered(concurrency)
+                .try_collect::<Vec<_>>().await?;
+            for mut page in pages {
+                items.append(&mut page.items);
+            }
+            Ok(items)
+        }
+        _ => {
+            let mut items = Vec::new();
+            let mut next_page = Some(1);
+            while let Some(page) = next_page {
+                let mut list = fetch_page(ListOptions { page: Some(page), ..options.clone() }).await?;
+                next_page = list.next_page();
+                items.append(&mut list.items);
+            }
+            Ok(items)
+        }
+    }
+}
+
 /// The service responsible for fetching records.
+#[derive(Clone)]
 pub struct RecordService<T>
 where T: DeserializeOwned + Clone {
     pub(crate) collection_id_or_name: String,
@@ -44,32 +103,93 @@ struct RecordIdOnly {
     id: String,
 }
 
+/// Builds the headers holding the bearer token of the [AuthStore](crate::AuthStore).
+/// If the auto refresh is enabled and the token is about to expire, it's refreshed first.
+/// A failed refresh is silently ignored, the request will simply be sent with the current token.
+pub(crate) async fn refreshed_auth_headers<T>(pb: &Arc<PocketBaseRef<T>>) -> HeaderMap
+where T: DeserializeOwned + Clone {
+    let window = *pb.auto_refresh.lock().unwrap();
+    if let Some(window) = window
+        && pb.needs_refresh(window) {
+        let _guard = pb.refresh_lock.lock().await;
+        // Another request might have refreshed the token while this one was waiting.
+        let collection = pb.auth_store.lock().unwrap().collection_name.clone();
+        if pb.needs_refresh(window) && let Some(collection) = collection {
+            let service = RecordService {
+                collection_id_or_name: collection,
+                pb: pb.clone(),
+            };
+            let _ = service.auth_refresh().await;
+        }
+    }
+    pb.auth_headers()
+}
+
 impl<T> RecordService<T>
 where T: DeserializeOwned + Clone {
-    async fn handle_response_body<E: DeserializeOwned>(&self, body: &String) -> Result<E, ApiError> {
-        let response = serde_json::from_str::<E>(body);
-        if response.is_ok() {
-            Ok(response.unwrap())
-        } else {
-            let error = serde_json::from_str::<ResponseError>(body).unwrap();
-            Err(ApiError::Http(StatusCode::from_u16(error.status).unwrap(), error.message))
+    async fn handle_response_body<E: DeserializeOwned>(&self, body: &str) -> Result<E, ApiError> {
+        handle_response_body(body)
+    }
+
+    async fn get_auth_headers(&self) -> HeaderMap {
+        refreshed_auth_headers(&self.pb).await
+    }
+
+    /// Sends a POST request to a route of the collection that answers with `204 No Content`.
+    async fn post_without_content<S: Serialize>(&self, route: &str, payload: &S) -> Result<(), ApiError> {
+        let url = format!("{}/api/collections/{}/{}", self.pb.base_url, self.collection_id_or_name, route);
+        let headers = self.get_auth_headers().await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .json(payload)
+            .send().await?
+            .text().await?;
+        handle_empty_response_body(&body)
+    }
+
+    /// If the updated record is the one of the current user,
+    /// then the response is stored as the record of the auth store.
+    async fn save_if_current_record(&self, body: &str) {
+        let is_current_record = {
+            let auth_store = self.pb.auth_store.lock().unwrap();
+            if auth_store.is_some() {
+                let auth_coll_id = auth_store.collection_id.clone().unwrap();
+                let auth_coll_name = auth_store.collection_name.clone().unwrap();
+                let record = serde_json::from_str::<RecordIdOnly>(body);
+                (self.collection_id_or_name == auth_coll_id || self.collection_id_or_name == auth_coll_name)
+                    && record.is_ok_and(|record| record.id == auth_store.record_id.clone().unwrap())
+            } else {
+                false
+            }
+        };
+        if is_current_record && let Ok(raw_record) = serde_json::from_str::<serde_json::Value>(body) {
+            // A partial record (because of the "fields" option) is simply not stored.
+            let _ = self.pb.save_auth_record(raw_record).await;
         }
     }
 
-    fn get_auth_headers(&self) -> HeaderMap {
-        let store = self.pb.auth_store.lock();
-        let token = store.as_ref().unwrap().token.clone();
-        let mut headers: HeaderMap = HeaderMap::new();
-        if let Some(token) = token {
-            headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+    /// Parses the response of an authentication route
+    /// and stores a duplicate of its content into the `AuthStore` instance of pocketbase.
+    async fn handle_auth_response_body(&self, body: &str) -> Result<AuthResponse<T>, ApiError> {
+        if let Ok(challenge) = serde_json::from_str::<MfaChallenge>(body) {
+            return Err(ApiError::MfaRequired { mfa_id: challenge.mfa_id });
         }
-        headers
+        let result = self.handle_response_body::<AuthResponse<T>>(body).await?;
+        let raw = self.handle_response_body::<AuthResponse<serde_json::Value>>(body).await?;
+        self.pb.save_auth(raw.token, raw.record).await?;
+        Ok(result)
     }
 
     /// Fetches pages of records.
     pub async fn get_list<E: DeserializeOwned>(&self, options: ListOptions) -> Result<ListResponse<E>, ApiError> {
-        let url = format!("{}/api/collections/{}/records{}", self.pb.base_url, self.collection_id_or_name, options.to_url_query());
-        let headers = self.get_auth_headers();
+        self.get_list_with_query(options, &[]).await
+    }
+
+    /// Fetches pages of records, with additional query parameters.
+    async fn get_list_with_query<E: DeserializeOwned>(&self, options: ListOptions, query: &[(String, String)]) -> Result<ListResponse<E>, ApiError> {
+        let url = format!("{}/api/collections/{}/records{}", self.pb.base_url, self.collection_id_or_name, options.to_url_query_with(query));
+        let headers = self.get_auth_headers().await;
         let body = self.pb.client
             .get(&url)
             .headers(headers)
@@ -82,7 +202,7 @@ where T: DeserializeOwned + Clone {
     /// If the ID isn't found, the server will return a 404 error.
     pub async fn get_one<E: DeserializeOwned>(&self, id: impl Into<String>, options: Option<ViewOptions>) -> Result<E, ApiError> {
         let url = format!("{}/api/collections/{}/records/{}{}", self.pb.base_url, self.collection_id_or_name, encode(&id.into()), options.unwrap_or_default().to_url_query());
-        let headers = self.get_auth_headers();
+        let headers = self.get_auth_headers().await;
         let body = self.pb.client
             .get(&url)
             .headers(headers)
@@ -91,26 +211,60 @@ where T: DeserializeOwned + Clone {
         self.handle_response_body(&body).await
     }
 
-    /// Gets the full list of records from the collection.
+    /// Gets the full list of records from the collection,
+    /// by fetching all the pages one after the other.
     pub async fn get_full_list<E: DeserializeOwned>(&self) -> Result<Vec<E>, ApiError> {
-        let mut page_index = 1u64;
-        let mut items: Vec<E> = Vec::new();
-        loop {
-            let pages = self.get_list::<E>(ListOptions::paginated_and_skip(page_index, 1000)).await;
-            if let Err(err) = pages {
-                return Err(err);
-            }
-            if let Ok(mut page) = pages {
-                let number_of_fetched_items = page.items.len();
-                items.append(&mut page.items);
-                if number_of_fetched_items == page.per_page as usize {
-                    page_index += 1;
-                } else {
-                    break;
-                }
+        self.get_full_list_with(FullListOptions::default()).await
+    }
+
+    /// Same as [RecordService::get_full_list], with options applied to every page request,
+    /// such as a filter or the number of records fetched per request.
+    pub async fn get_full_list_with<E: DeserializeOwned>(&self, options: FullListOptions) -> Result<Vec<E>, ApiError> {
+        let concurrency = options.concurrency;
+        let (options, query) = options.into_parts();
+        fetch_full_list(options, concurrency, |options| self.get_list_with_query(options, &query)).await
+    }
+
+    /// Walks through all the records matching the options, one at a time,
+    /// fetching the pages lazily so that only one page is kept in memory.
+    ///
+    /// The size of the pages is `per_page` (1000 by default),
+    /// and the walk starts at `page` (the first page by default).
+    ///
+    /// ```rust,no_run
+    /// # use pbrsdk::*;
+    /// # use futures::StreamExt;
+    /// # async fn run(pb: PocketBase<DefaultAuthRecord>) -> Result<(), ApiError> {
+    /// let options = ListOptions { per_page: Some(500), sort: Some("created".to_string()), ..ListOptions::default() };
+    /// let mut records = std::pin::pin!(pb.collection("articles").stream::<serde_json::Value>(options));
+    /// while let Some(record) = records.next().await {
+    ///     println!("{}", record?["id"]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream<E: DeserializeOwned>(&self, options: ListOptions) -> impl Stream<Item = Result<E, ApiError>> + use<T, E> {
+        let service = RecordService {
+            collection_id_or_name: self.collection_id_or_name.clone(),
+            pb: self.pb.clone(),
+        };
+        let per_page = options.per_page.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+        let first_page = options.page.unwrap_or(1);
+        let options = ListOptions {
+            per_page: Some(per_page),
+            skip_total: Some(options.skip_total.unwrap_or(true)),
+            ..options
+        };
+        stream::try_unfold(Some(first_page), move |page| {
+            let service = service.clone();
+            let options = options.clone();
+            async move {
+                let Some(page) = page else { return Ok::<_, ApiError>(None) };
+                let list = service.get_list::<E>(ListOptions { page: Some(page), ..options }).await?;
+                let next_page = list.next_page();
+                Ok(Some((stream::iter(list.items.into_iter().map(Ok)), next_page)))
             }
-        }
-        Ok(items)
+        }).try_flatten()
     }
 
     /// Returns the first found item by the specified filter.
@@ -133,7 +287,7 @@ where T: DeserializeOwned + Clone {
     /// Creates a new item and returns the new record.
     pub async fn create<E: DeserializeOwned, S: Serialize>(&self, body: S, options: Option<ViewOptions>) -> Result<E, ApiError> {
         let url = format!("{}/api/collections/{}/records{}", self.pb.base_url, self.collection_id_or_name, options.unwrap_or_default().to_url_query());
-        let headers = self.get_auth_headers();
+        let headers = self.get_auth_headers().await;
         let body = self.pb.client
             .post(&url)
             .headers(headers)
@@ -143,88 +297,311 @@ where T: DeserializeOwned + Clone {
         self.handle_response_body(&body).await
     }
 
+    /// Creates a new item with files and returns the new record.
+    /// The body is sent as `multipart/form-data`, see [RecordFiles].
+    /// Fails with [ApiError::Json] if the body isn't serialized as a JSON object.
+    pub async fn create_with_files<E: DeserializeOwned, S: Serialize>(&self, body: S, files: RecordFiles, options: Option<ViewOptions>) -> Result<E, ApiError> {
+        let url = format!("{}/api/collections/{}/records{}", self.pb.base_url, self.collection_id_or_name, options.unwrap_or_default().to_url_query());
+        let form = files.into_form(body).await?;
+        let headers = self.get_auth_headers().await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .multipart(form)
+            .send().await?
+            .text().await?;
+        self.handle_response_body(&body).await
+    }
+
     /// Deletes an existing item by its id.
     /// Returns nothing if the operation succeeds.
     pub async fn delete(&self, id: impl Into<String>) -> Result<(), ApiError> {
         let url = format!("{}/api/collections/{}/records/{}", self.pb.base_url, self.collection_id_or_name, encode(&id.into()));
-        let headers = self.get_auth_headers();
+        let headers = self.get_auth_headers().await;
         let body = self.pb.client
             .delete(&url)
             .headers(headers)
             .send().await?
             .text().await?;
-        if body.is_empty() {
-            Ok(())
-        } else {
-            let error = serde_json::from_str::<ResponseError>(&body);
-            if let Ok(error) = error {
-                Err(ApiError::Http(StatusCode::from_u16(error.status).unwrap(), error.message))
-            } else {
-                Ok(())
-            }
-        }
+        handle_empty_response_body(&body)
     }
 
     /// Updates an existing item by its ID.
     pub async fn update<E: DeserializeOwned, S: Serialize>(&self, id: impl Into<String>, body: S, options: Option<ViewOptions>) -> Result<E, ApiError> {
         // TODO: handle reauthentication if the update changes the password of the current user ?
         let url = format!("{}/api/collections/{}/records/{}{}", self.pb.base_url, self.collection_id_or_name, encode(&id.into()), options.unwrap_or_default().to_url_query());
-        let headers = self.get_auth_headers();
+        let headers = self.get_auth_headers().await;
         let body = self.pb.client
             .patch(&url)
             .headers(headers)
             .json(&body)
             .send().await?
             .text().await?;
-        // If the update concerns the current user,
-        // then the response is stored as the record of the auth store.
-        let mut auth_store = self.pb.auth_store.lock().unwrap();
-        if auth_store.is_some() {
-            let auth_coll_id = auth_store.collection_id.clone().unwrap();
-            let auth_coll_name = auth_store.collection_name.clone().unwrap();
-            if self.collection_id_or_name == auth_coll_id || self.collection_id_or_name == auth_coll_name {
-                let record = serde_json::from_str::<RecordIdOnly>(&body);
-                if let Ok(record) = record {
-                    let auth_rec_id = auth_store.record_id.clone().unwrap();
-                    if record.id == auth_rec_id {
-                        auth_store.set_record_id(record.id.clone());
-                        let auth_record = serde_json::from_str::<T>(&body);
-                        if let Ok(auth_record) = auth_record {
-                            auth_store.set_record(auth_record);
-                        }
-                    }
-                }
-            }
-        }
+        self.save_if_current_record(&body).await;
+        self.handle_response_body(&body).await
+    }
+
+    /// Updates an existing item by its ID, uploading or removing files at the same time.
+    /// The body is sent as `multipart/form-data`, see [RecordFiles].
+    /// Fails with [ApiError::Json] if the body isn't serialized as a JSON object.
+    pub async fn update_with_files<E: DeserializeOwned, S: Serialize>(&self, id: impl Into<String>, body: S, files: RecordFiles, options: Option<ViewOptions>) -> Result<E, ApiError> {
+        let url = format!("{}/api/collections/{}/records/{}{}", self.pb.base_url, self.collection_id_or_name, encode(&id.into()), options.unwrap_or_default().to_url_query());
+        let form = files.into_form(body).await?;
+        let headers = self.get_auth_headers().await;
+        let body = self.pb.client
+            .patch(&url)
+            .headers(headers)
+            .multipart(form)
+            .send().await?
+            .text().await?;
+        self.save_if_current_record(&body).await;
         self.handle_response_body(&body).await
     }
 
     /// Authenticates using an identity field (usually an email address) and a password.
     ///
     /// Stores a duplicate of the user's record into the `AuthStore` instance of pocketbase.
+    ///
+    /// If the collection requires MFA, it fails with [ApiError::MfaRequired],
+    /// see [RecordService::auth_with_password_options].
     pub async fn auth_with_password(&mut self, identity: impl Into<String>, password: impl Into<String>) -> Result<AuthResponse<T>, ApiError> {
-        let url = format!("{}/api/collections/{}/auth-with-password", self.pb.base_url, self.collection_id_or_name);
+        self.auth_with_password_options(identity, password, AuthOptions::default()).await
+    }
+
+    /// Same as [RecordService::auth_with_password], with options.
+    ///
+    /// If the collection requires MFA, the first authentication fails with [ApiError::MfaRequired],
+    /// and the second one must be given the `mfa_id` through the options.
+    pub async fn auth_with_password_options(&mut self, identity: impl Into<String>, password: impl Into<String>, options: AuthOptions) -> Result<AuthResponse<T>, ApiError> {
+        let url = format!("{}/api/collections/{}/auth-with-password{}", self.pb.base_url, self.collection_id_or_name, options.to_url_query());
         let payload = AuthRequestPayload {
             password: password.into(),
             identity: identity.into(),
         };
         let body = self.pb.client.post(&url).header("Content-Type", "application/json").json(&payload).send().await?.text().await?;
-        let tmp = self.handle_response_body::<AuthResponse<DefaultAuthResponseRecord>>(&body).await;
-        let result = self.handle_response_body::<AuthResponse<T>>(&body).await;
-        if let Ok(response) = &tmp {
-            let token = response.token.clone();
-            let mut lock = self.pb.auth_store.lock().unwrap();
-            if let Ok(actual_result) = &result {
-                lock.set_token(token);
-                lock.set_collection(response.record.collection_name.clone(), response.record.collection_id.clone());
-                lock.set_record_id(response.record.id.clone());
-                lock.set_record(actual_result.record.clone());
-            } else {
-                return Err(result.err().unwrap());
-            }
+        self.handle_auth_response_body(&body).await
+    }
+
+    /// Sends an email containing a one-time password to the user with the given email address.
+    /// Returns the ID of the OTP, which must be given to [RecordService::auth_with_otp] along with the password.
+    ///
+    /// For security reasons, the server returns an ID even if there is no user with this email address.
+    pub async fn request_otp(&self, email: impl Into<String>) -> Result<String, ApiError> {
+        let url = format!("{}/api/collections/{}/request-otp", self.pb.base_url, self.collection_id_or_name);
+        let payload = EmailPayload {
+            email: email.into(),
+        };
+        let body = self.pb.client.post(&url).json(&payload).send().await?.text().await?;
+        let response = self.handle_response_body::<OtpResponse>(&body).await?;
+        Ok(response.otp_id)
+    }
+
+    /// Authenticates using the ID returned by [RecordService::request_otp]
+    /// and the one-time password that the user received by email.
+    ///
+    /// Stores a duplicate of the user's record into the `AuthStore` instance of pocketbase.
+    ///
+    /// If the collection requires MFA, it fails with [ApiError::MfaRequired],
+    /// see [RecordService::auth_with_otp_options].
+    pub async fn auth_with_otp(&self, otp_id: impl Into<String>, password: impl Into<String>) -> Result<AuthResponse<T>, ApiError> {
+        self.auth_with_otp_options(otp_id, password, AuthOptions::default()).await
+    }
+
+    /// Same as [RecordService::auth_with_otp], with options.
+    ///
+    /// If the collection requires MFA, the first authentication fails with [ApiError::MfaRequired],
+    /// and the second one must be given the `mfa_id` through the options.
+    pub async fn auth_with_otp_options(&self, otp_id: impl Into<String>, password: impl Into<String>, options: AuthOptions) -> Result<AuthResponse<T>, ApiError> {
+        let url = format!("{}/api/collections/{}/auth-with-otp{}", self.pb.base_url, self.collection_id_or_name, options.to_url_query());
+        let payload = OtpAuthPayload {
+            otp_id: otp_id.into(),
+            password: password.into(),
+        };
+        let body = self.pb.client.post(&url).json(&payload).send().await?.text().await?;
+        self.handle_auth_response_body(&body).await
+    }
+
+    /// Sends an email to the user, containing a link to reset their password.
+    pub async fn request_password_reset(&self, email: impl Into<String>) -> Result<(), ApiError> {
+        let payload = EmailPayload {
+            email: email.into(),
+        };
+        self.post_without_content("request-password-reset", &payload).await
+    }
+
+    /// Sets the new password of the user, using the token that was sent by email.
+    ///
+    /// Note that all the tokens previously issued for this user become invalid.
+    pub async fn confirm_password_reset(&self, token: impl Into<String>, password: impl Into<String>, password_confirm: impl Into<String>) -> Result<(), ApiError> {
+        let payload = ConfirmPasswordResetPayload {
+            token: token.into(),
+            password: password.into(),
+            password_confirm: password_confirm.into(),
+        };
+        self.post_without_content("confirm-password-reset", &payload).await
+    }
+
+    /// Sends an email to the user, containing a link to verify their email address.
+    pub async fn request_verification(&self, email: impl Into<String>) -> Result<(), ApiError> {
+        let payload = EmailPayload {
+            email: email.into(),
+        };
+        self.post_without_content("request-verification", &payload).await
+    }
+
+    /// Marks the user as verified, using the token that was sent by email.
+    ///
+    /// If the token belongs to the current user,
+    /// then the record of the auth store is marked as verified too.
+    pub async fn confirm_verification(&self, token: impl Into<String>) -> Result<(), ApiError> {
+        let payload = TokenPayload {
+            token: token.into(),
+        };
+        self.post_without_content("confirm-verification", &payload).await?;
+        if let Some(mut record) = self.current_raw_record(&payload.token) {
+            record["verified"] = serde_json::Value::Bool(true);
+            // The change is already made on the server, so failing to store it locally isn't an error.
+            let _ = self.pb.save_auth_record(record).await;
+        }
+        Ok(())
+    }
+
+    /// Sends an email to the new address of the current user, containing a link to confirm the change.
+    /// The user must be authenticated.
+    pub async fn request_email_change(&self, new_email: impl Into<String>) -> Result<(), ApiError> {
+        let payload = EmailChangePayload {
+            new_email: new_email.into(),
+        };
+        self.post_without_content("request-email-change", &payload).await
+    }
+
+    /// Changes the email address of the user, using the token that was sent to the new address.
+    ///
+    /// If the token belongs to the current user, then the auth store is cleared:
+    /// the server invalidates all the tokens of the user on an email change,
+    /// so the user must authenticate again.
+    pub async fn confirm_email_change(&self, token: impl Into<String>, password: impl Into<String>) -> Result<(), ApiError> {
+        let payload = ConfirmEmailChangePayload {
+            token: token.into(),
+            password: password.into(),
+        };
+        self.post_without_content("confirm-email-change", &payload).await?;
+        let is_current_record = self.pb.auth_store.lock().unwrap().is_token_of_current_record(&payload.token);
+        if is_current_record {
+            self.pb.clear_auth().await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the raw record of the auth store if the token belongs to the current user.
+    fn current_raw_record(&self, token: &str) -> Option<serde_json::Value> {
+        let auth_store = self.pb.auth_store.lock().unwrap();
+        if auth_store.is_token_of_current_record(token) {
+            auth_store.raw_record.clone().filter(|record| record.is_object())
         } else {
-            return Err(result.err().unwrap());
+            None
+        }
+    }
+
+    /// Authenticates as another user of the collection, which requires to be authenticated as a superuser.
+    ///
+    /// Returns a new [PocketBase] instance whose `AuthStore` holds the impersonated user,
+    /// so that the current instance remains authenticated as the superuser.
+    /// If `duration` is zero, the token lasts as long as the regular tokens of the collection.
+    ///
+    /// Note that the impersonated token cannot be refreshed.
+    pub async fn impersonate(&self, record_id: impl Into<String>, duration: Duration) -> Result<PocketBase<T>, ApiError> {
+        let url = format!("{}/api/collections/{}/impersonate/{}", self.pb.base_url, self.collection_id_or_name, encode(&record_id.into()));
+        let payload = ImpersonatePayload {
+            duration: duration.as_secs(),
+        };
+        let headers = self.get_auth_headers().await;
+        let body = self.pb.client
+            .post(&url)
+            .headers(headers)
+            .json(&payload)
+            .send().await?
+            .text().await?;
+        let impersonated = PocketBase::from_client(self.pb.base_url.clone(), self.pb.client.clone(), Arc::new(InMemoryBackend::default()));
+        impersonated.collection(self.collection_id_or_name.clone()).handle_auth_response_body(&body).await?;
+        Ok(impersonated)
+    }
+
+    /// Lists the authentication methods allowed by the collection,
+    /// including the OAuth2 providers and the data needed to use them.
+    pub async fn list_auth_methods(&self) -> Result<AuthMethodsList, ApiError> {
+        let url = format!("{}/api/collections/{}/auth-methods", self.pb.base_url, self.collection_id_or_name);
+        let body = self.pb.client
+            .get(&url)
+            .send().await?
+            .text().await?;
+        self.handle_response_body(&body).await
+    }
+
+    /// Authenticates with an OAuth2 provider by exchanging the code
+    /// that the provider gave to the redirect URL.
+    ///
+    /// - `provider` is the name of the provider, as listed by [RecordService::list_auth_methods].
+    /// - `code_verifier` is the one given by [RecordService::list_auth_methods] for this provider.
+    /// - `redirect_url` must be the same as the one given to the provider.
+    /// - `create_data` is used to fill the fields of the record if it doesn't exist yet.
+    ///
+    /// Stores a duplicate of the user's record into the `AuthStore` instance of pocketbase.
+    pub async fn auth_with_oauth2_code(&self, provider: impl Into<String>, code: impl Into<String>, code_verifier: impl Into<String>, redirect_url: impl Into<String>, create_data: Option<serde_json::Value>) -> Result<AuthResponse<T>, ApiError> {
+        let url = format!("{}/api/collections/{}/auth-with-oauth2", self.pb.base_url, self.collection_id_or_name);
+        let payload = OAuth2CodePayload {
+            provider: provider.into(),
+            code: code.into(),
+            code_verifier: code_verifier.into(),
+            redirect_url: redirect_url.into(),
+            create_data,
+        };
+        let body = self.pb.client.post(&url).json(&payload).send().await?.text().await?;
+        self.handle_auth_response_body(&body).await
+    }
+
+    /// Refreshes the token of the current user, which must be authenticated with this collection.
+    /// The new token and the up-to-date record are stored into the `AuthStore` instance of pocketbase.
+    ///
+    /// Note that tokens obtained through impersonation cannot be refreshed.
+    pub async fn auth_refresh(&self) -> Result<AuthResponse<T>, ApiError> {
+        let url = format!("{}/api/collections/{}/auth-refresh", self.pb.base_url, self.collection_id_or_name);
+        let body = self.pb.client
+            .post(&url)
+            .headers(self.pb.auth_headers())
+            .send().await?
+            .text().await?;
+        self.handle_auth_response_body(&body).await
+    }
+
+    /// Subscribes to the realtime changes of the records of the collection.
+    /// Use `"*"` as the topic to be notified of the changes of all the records,
+    /// or the ID of a record to only be notified of the changes of this particular record.
+    ///
+    /// The realtime connection is shared by all the services of the [PocketBase](crate::PocketBase) instance.
+    pub async fn subscribe<E, F>(&self, topic: impl Into<String>, callback: F) -> Result<(), ApiError>
+    where T: Send + Sync + 'static, E: DeserializeOwned + 'static, F: Fn(RecordSubscription<E>) + Send + Sync + 'static {
+        let topic = format!("{}/{}", self.collection_id_or_name, topic.into());
+        self.realtime().subscribe(topic, callback).await
+    }
+
+    /// Removes all the listeners of the given topic (`"*"` or a record ID) of the collection.
+    pub async fn unsubscribe(&self, topic: impl Into<String>) -> Result<(), ApiError>
+    where T: Send + Sync + 'static {
+        let topic = format!("{}/{}", self.collection_id_or_name, topic.into());
+        self.realtime().unsubscribe(topic).await
+    }
+
+    /// Removes all the listeners of all the topics of the collection.
+    pub async fn unsubscribe_all(&self) -> Result<(), ApiError>
+    where T: Send + Sync + 'static {
+        let prefix = format!("{}/", self.collection_id_or_name);
+        self.realtime().unsubscribe_by_prefix(prefix).await
+    }
+
+    fn realtime(&self) -> RealtimeService<T> {
+        RealtimeService {
+            pb: self.pb.clone(),
         }
-        result
     }
 }
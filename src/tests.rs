@@ -84,11 +84,77 @@ static DEMO: Lazy<TestData> = Lazy::new(|| {
 });
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use serial_test::serial;
     use super::*;
     use crate::*;
-    use crate::cookies::cookie_parse;
+    use crate::cookies::cookie_value;
+    use crate::services::realtime_service::drain_sse_events;
+    use crate::auth::{is_token_expiring, is_token_refreshable};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use std::sync::Arc;
+    use futures::{StreamExt, TryStreamExt};
+
+    fn fake_auth_data() -> AuthData {
+        AuthData {
+            token: fake_token(3600, true),
+            record: serde_json::json!({
+                "id": "abc",
+                "collectionId": "_pb_users_auth_",
+                "collectionName": "users",
+                "email": "john@example.com",
+                "verified": true,
+                "emailVisibility": false,
+                "created": "2026-01-28 10:02:31.589Z",
+                "updated": "2026-01-28 10:02:31.589Z",
+                "name": "John"
+            }),
+        }
+    }
+
+    /// Starts a server answering every request with the JSON returned by the handler,
+    /// which receives the path and the query of the request. Returns its base URL.
+    fn mock_server(handler: fn(&str) -> String) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let body = handler(request_line.split(' ').nth(1).unwrap_or_default());
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    /// Answers the list requests like PocketBase would with 2500 records,
+    /// capping `perPage` at 1000.
+    fn mock_list_of_2500_records(path: &str) -> String {
+        let param = |name: &str| path.split(['?', '&']).find_map(|x| x.strip_prefix(&format!("{}=", name))).and_then(|x| x.parse::<u64>().ok());
+        let page = param("page").unwrap_or(1);
+        let per_page = param("perPage").unwrap_or(30).min(1000);
+        let (total_items, total_pages) = if param("skipTotal") == Some(1) { (-1, -1) } else { (2500, 2500_u64.div_ceil(per_page) as i64) };
+        let items = ((page - 1) * per_page..(page * per_page).min(2500)).map(|x| serde_json::json!({ "id": x.to_string(), "name": format!("c{}", x), "type": "base" })).collect::<Vec<_>>();
+        serde_json::json!({ "items": items, "page": page, "perPage": per_page, "totalItems": total_items, "totalPages": total_pages }).to_string()
+    }
+
+    /// Builds an unsigned token that expires in the given number of seconds.
+    fn fake_token(expires_in: u64, refreshable: bool) -> String {
+        let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + expires_in;
+        let payload = format!("{{\"collectionId\":\"_pb_users_auth_\",\"exp\":{},\"id\":\"abc\",\"refreshable\":{},\"type\":\"auth\"}}", exp, refreshable);
+        format!("{}.{}.signature", URL_SAFE_NO_PAD.encode("{\"alg\":\"HS256\",\"typ\":\"JWT\"}"), URL_SAFE_NO_PAD.encode(payload))
+    }
 
     #[test]
     fn test_demo_data() {
@@ -106,6 +172,14 @@ mod tests {
         assert_eq!(pb2.base_url(), "http://localhost:8091");
     }
 
+    #[test]
+    fn test_base_url_without_trailing_slash() {
+        let pb = PocketBase::default("http://localhost:8091").unwrap();
+        assert_eq!(pb.base_url(), "http://localhost:8091");
+        let pb = PocketBase::<DefaultAuthRecord>::with_backend("http://localhost:8091", InMemoryBackend::default()).unwrap();
+        assert_eq!(pb.base_url(), "http://localhost:8091");
+    }
+
     #[test]
     fn test_empty_auth_store() {
         let pb = PocketBase::default("http://localhost:8091/").unwrap();
@@ -143,6 +217,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::len_zero)]
     #[tokio::test]
     #[serial]
     async fn test_authless_get_list_with_filter() {
@@ -164,6 +239,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::len_zero)]
     #[tokio::test]
     #[serial]
     async fn test_authless_get_list_with_filter_and_skip_total() {
@@ -186,6 +262,7 @@ mod tests {
         }
     }
 
+    #[allow(clippy::len_zero)]
     #[tokio::test]
     #[serial]
     async fn test_authless_get_list_paginated() {
@@ -222,6 +299,99 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_authless_get_full_list_with_options() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let options = FullListOptions {
+            batch: Some(1),
+            filter: Some("public=true".to_string()),
+            sort: Some("-price".to_string()),
+            ..FullListOptions::default()
+        };
+        let fetched_records = pb.collection("articles").get_full_list_with::<ArticleRecord>(options).await.expect("Could not fetch articles.");
+        assert_eq!(fetched_records.len(), DEMO.data.articles.iter().filter(|x| x.public).count());
+        assert!(fetched_records.iter().all(|x| x.public));
+        assert!(fetched_records.windows(2).all(|x| x[0].price >= x[1].price));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_authless_get_full_list_concurrently() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let options = FullListOptions { batch: Some(1), sort: Some("id".to_string()), ..FullListOptions::default() };
+        let sequential_records = pb.collection("articles").get_full_list_with::<ArticleRecord>(options.clone()).await.expect("Could not fetch articles.");
+        let options = FullListOptions { concurrency: Some(3), ..options };
+        let concurrent_records = pb.collection("articles").get_full_list_with::<ArticleRecord>(options).await.expect("Could not fetch articles.");
+        assert_eq!(concurrent_records.len(), DEMO.data.articles.len());
+        assert_eq!(concurrent_records.iter().map(|x| &x.id).collect::<Vec<_>>(), sequential_records.iter().map(|x| &x.id).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_get_full_list_with_capped_batch() {
+        let pb = PocketBase::default(mock_server(mock_list_of_2500_records)).unwrap();
+        let options = FullListOptions { batch: Some(5000), ..FullListOptions::default() };
+        let records = pb.collection("articles").get_full_list_with::<serde_json::Value>(options.clone()).await.expect("Could not fetch records.");
+        assert_eq!(records.len(), 2500);
+        let options = FullListOptions { concurrency: Some(2), ..options };
+        let records = pb.collection("articles").get_full_list_with::<serde_json::Value>(options).await.expect("Could not fetch records.");
+        assert_eq!(records.len(), 2500);
+        assert!(records.iter().enumerate().all(|(i, x)| x["id"].as_str() == Some(i.to_string().as_str())));
+    }
+
+    #[tokio::test]
+    async fn test_collections_get_full_list_concurrently() {
+        let pb = PocketBase::default(mock_server(mock_list_of_2500_records)).unwrap();
+        let collections = pb.collections().get_full_list().await.expect("Could not fetch collections.");
+        assert_eq!(collections.len(), 2500);
+        let options = FullListOptions { batch: Some(300), concurrency: Some(3), ..FullListOptions::default() };
+        let concurrent_collections = pb.collections().get_full_list_with(options).await.expect("Could not fetch collections.");
+        assert_eq!(concurrent_collections, collections);
+    }
+
+    #[test]
+    fn test_full_list_options_query() {
+        let options = FullListOptions {
+            batch: Some(200),
+            filter: Some("public=true".to_string()),
+            expand: Some("author".to_string()),
+            query: vec![("lang".to_string(), "fr BE".to_string())],
+            ..FullListOptions::default()
+        };
+        let (options, query) = options.into_parts();
+        let options = ListOptions { page: Some(2), ..options };
+        assert_eq!(options.to_url_query_with(&query), "?page=2&perPage=200&skipTotal=1&filter=public%3Dtrue&expand=author&lang=fr%20BE");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_authless_stream() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let options = ListOptions { per_page: Some(2), ..ListOptions::default() };
+        let streamed_records = pb.collection("articles").stream::<ArticleRecord>(options).collect::<Vec<_>>().await;
+        assert_eq!(streamed_records.len(), DEMO.data.articles.len());
+        for article in streamed_records {
+            let article = article.expect("Could not fetch article.");
+            DEMO.data.articles.iter().find(|x| { x.id == article.id }).expect("Missing demo article");
+        }
+        let options = ListOptions { filter: Some("name = 'nothing at all'".to_string()), ..ListOptions::default() };
+        assert_eq!(pb.collection("articles").stream::<ArticleRecord>(options).count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_with_capped_per_page() {
+        let pb = PocketBase::default(mock_server(mock_list_of_2500_records)).unwrap();
+        let options = ListOptions { per_page: Some(5000), ..ListOptions::default() };
+        let records = pb.collection("articles").stream::<serde_json::Value>(options).try_collect::<Vec<_>>().await.expect("Could not stream records.");
+        assert_eq!(records.len(), 2500);
+        assert_eq!(records[2499]["id"], "2499");
+        let page = ListResponse { items: vec![0; 1000], page: 1, per_page: 1000, total_items: -1, total_pages: -1 };
+        assert_eq!(page.next_page(), Some(2));
+        let page = ListResponse { items: vec![0; 500], page: 3, per_page: 1000, total_items: -1, total_pages: -1 };
+        assert_eq!(page.next_page(), None);
+    }
+
+    #[allow(clippy::len_zero)]
     #[tokio::test]
     #[serial]
     async fn test_authless_get_list_and_sort() {
@@ -308,6 +478,7 @@ mod tests {
         assert!(auth_store.is_superuser());
     }
 
+    #[allow(clippy::let_unit_value)]
     #[tokio::test]
     #[serial]
     async fn test_delete_and_create() {
@@ -335,6 +506,154 @@ mod tests {
         assert_ne!(created_record.created, demo.created);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_batch() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        // The batch API is disabled in the settings of the demo server.
+        let settings = reqwest::Client::new()
+            .patch(format!("{}/api/settings", pb.base_url()))
+            .bearer_auth(pb.auth_store().token.unwrap())
+            .json(&serde_json::json!({ "batch": { "enabled": true } }))
+            .send().await.expect("Could not enable the batch API.");
+        assert!(settings.status().is_success(), "Could not enable the batch API.");
+        let mut batch = pb.create_batch();
+        assert!(batch.is_empty());
+        batch.collection("articles").create(ArticleUpdatePayload { name: "Batch 1".to_string() }, None).unwrap();
+        batch.collection("articles").upsert(serde_json::json!({ "id": "batchupsert0001", "name": "Batch 2" }), None).unwrap();
+        assert_eq!(batch.len(), 2);
+        let results = batch.send().await.expect("Could not send batch.");
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|x| x.status == 200));
+        let first = results[0].record::<ArticleRecord>().unwrap();
+        assert_eq!(first.name, "Batch 1");
+        let mut batch = pb.create_batch();
+        batch.collection("articles").update("batchupsert0001", ArticleUpdatePayload { name: "Batch 3".to_string() }, None).unwrap();
+        batch.collection("articles").delete("batchupsert0001");
+        batch.collection("articles").delete(first.id.clone());
+        batch.collection("articles").delete("doesnotexist123");
+        assert!(matches!(batch.send().await, Err(ApiError::Batch { .. })), "The whole batch should fail.");
+        assert!(pb.collection("articles").get_one::<ArticleRecord>(first.id.clone(), None).await.is_ok(), "The batch should have been rolled back.");
+        let mut batch = pb.create_batch();
+        batch.collection("articles").delete("batchupsert0001");
+        batch.collection("articles").delete(first.id);
+        let results = batch.send().await.expect("Could not send batch.");
+        assert!(results.iter().all(|x| x.status == 204));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch() {
+        let pb = PocketBase::default(mock_server(|_| serde_json::json!({
+            "status": 400,
+            "message": "Batch transaction failed.",
+            "data": { "requests": { "1": { "code": "batch_request_failed", "message": "Batch request failed.", "response": { "status": 404, "message": "The requested resource wasn't found.", "data": {} } } } }
+        }).to_string())).unwrap();
+        let mut batch = pb.create_batch();
+        batch.collection("articles").delete("abc");
+        batch.collection("articles").delete("doesnotexist123");
+        let Err(ApiError::Batch { status, message, data }) = batch.send().await else { panic!("The batch should fail.") };
+        assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(message, "Batch transaction failed.");
+        assert_eq!(data["requests"]["1"]["response"]["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_error_status() {
+        let pb = PocketBase::default(mock_server(|_| "{\"status\":0,\"message\":\"Proxy error.\"}".to_string())).unwrap();
+        let Err(ApiError::Http(status, message)) = pb.collection("articles").get_one::<ArticleRecord>("abc", None).await else { panic!("The request should fail.") };
+        assert_eq!(status, reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(message, "Proxy error.");
+        assert!(matches!(pb.collection("articles").delete("abc").await, Err(ApiError::Http(reqwest::StatusCode::INTERNAL_SERVER_ERROR, _))));
+        let mut batch = pb.create_batch();
+        batch.collection("articles").delete("abc");
+        assert!(matches!(batch.send().await, Err(ApiError::Batch { status: reqwest::StatusCode::INTERNAL_SERVER_ERROR, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_record_files_form() {
+        /// A reader that can be sent to another thread but not shared between threads.
+        struct NotSyncReader(std::cell::Cell<u8>, &'static [u8]);
+
+        impl tokio::io::AsyncRead for NotSyncReader {
+            fn poll_read(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<std::io::Result<()>> {
+                std::pin::Pin::new(&mut self.1).poll_read(cx, buf)
+            }
+        }
+
+        let reader = NotSyncReader(std::cell::Cell::new(0), b"content");
+        assert_eq!(reader.0.get(), 0);
+        let files = RecordFiles::new().set("documents", FileUpload::from_reader("a.txt", reader));
+        assert!(files.into_form(serde_json::json!({ "title": "Hello" })).await.is_ok());
+        assert!(RecordFiles::new().remove("documents", ["a.txt"]).into_form(()).await.is_ok());
+        let err = RecordFiles::new().remove("documents", ["a.txt"]).into_form(vec!["title"]).await.unwrap_err();
+        assert!(matches!(err, ApiError::Json(_)));
+    }
+
+    #[test]
+    fn test_record_files_remove_twice() {
+        let mut files = RecordFiles::new().remove("documents", ["a.txt"]).remove("documents", ["b.txt"]).remove("images", ["c.png"]);
+        let payload = files.payload(serde_json::json!({ "title": "Hello", "documents-": ["d.txt"], "images-": "e.png" })).unwrap();
+        assert_eq!(payload, serde_json::json!({
+            "title": "Hello",
+            "documents-": ["d.txt", "a.txt", "b.txt"],
+            "images-": ["e.png", "c.png"],
+        }));
+        let mut files = RecordFiles::new().remove("documents", ["a.txt"]);
+        assert!(matches!(files.payload(serde_json::json!({ "documents-": 1 })), Err(ApiError::Json(_))));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_upload_files() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        let mut model = CollectionModel::new("pbrsdk_files", CollectionType::Base);
+        model.fields.push(Field::Text(TextField { base: FieldBase::new("title"), ..TextField::default() }));
+        model.fields.push(Field::File(FileField { base: FieldBase::new("documents"), max_select: 5, ..FileField::default() }));
+        let collection = pb.collections().create(&model).await.expect("Could not create collection.");
+        let files = RecordFiles::new()
+            .set("documents", FileUpload::from_bytes("a.txt", "first"))
+            .set("documents", FileUpload::from_path("Cargo.toml").mime_type("text/plain"));
+        let created: serde_json::Value = pb.collection("pbrsdk_files").create_with_files(serde_json::json!({ "title": "Hello" }), files, None).await.expect("Could not create record with files.");
+        assert_eq!(created["title"], "Hello");
+        let documents = created["documents"].as_array().unwrap();
+        assert_eq!(documents.len(), 2);
+        let first = documents[0].as_str().unwrap().to_string();
+        let files = RecordFiles::new()
+            .append("documents", FileUpload::from_reader("c.txt", std::io::Cursor::new(b"third".to_vec())))
+            .remove("documents", [first.clone()]);
+        let id = created["id"].as_str().unwrap();
+        let updated: serde_json::Value = pb.collection("pbrsdk_files").update_with_files(id, serde_json::json!({ "title": "World" }), files, None).await.expect("Could not update record with files.");
+        assert_eq!(updated["title"], "World");
+        let documents = updated["documents"].as_array().unwrap();
+        assert_eq!(documents.len(), 2);
+        assert!(!documents.iter().any(|x| x.as_str() == Some(first.as_str())));
+        assert!(documents[1].as_str().unwrap().starts_with("c_"));
+        pb.collections().delete(&collection.id).await.expect("Could not delete collection.");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_download_files() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        let mut model = CollectionModel::new("pbrsdk_downloads", CollectionType::Base);
+        model.fields.push(Field::File(FileField { base: FieldBase::new("document"), max_select: 1, ..FileField::default() }));
+        let collection = pb.collections().create(&model).await.expect("Could not create collection.");
+        let files = RecordFiles::new().set("document", FileUpload::from_bytes("a.txt", "content"));
+        let record: serde_json::Value = pb.collection("pbrsdk_downloads").create_with_files(serde_json::json!({}), files, None).await.expect("Could not create record with files.");
+        let mut stream = pb.files().download(&record, record["document"].as_str().unwrap(), None).await.expect("Could not download file.");
+        let mut content = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk.expect("Could not read chunk."));
+        }
+        assert_eq!(content, b"content");
+        assert!(!pb.files().get_token().await.expect("Could not get file token.").is_empty());
+        assert!(pb.files().download(&record, "missing.txt", None).await.is_err());
+        pb.collections().delete(&collection.id).await.expect("Could not delete collection.");
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_update() {
@@ -380,7 +699,793 @@ mod tests {
     #[test]
     fn test_cookie_parse() {
         let cookie = "pb_auth=%7B%22token%22%3A%22eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJjb2xsZWN0aW9uSWQiOiJwYmNfMzE0MjYzNTgyMyIsImV4cCI6MTc3MTUyOTIxNSwiaWQiOiJhNjl1bHdrMm15cHNqem8iLCJyZWZyZXNoYWJsZSI6dHJ1ZSwidHlwZSI6ImF1dGgifQ.azMQuCT026JPL5Lc6NobhdadtCVFRMc7pnvia7h2dG0%22%2C%22record%22%3A%7B%22collectionId%22%3A%22pbc_3142635823%22%2C%22collectionName%22%3A%22_superusers%22%2C%22created%22%3A%222026-01-28%2010%3A02%3A31.589Z%22%2C%22email%22%3A%22thomas%40gysemans.dev%22%2C%22emailVisibility%22%3Afalse%2C%22id%22%3A%22a69ulwk2mypsjzo%22%2C%22updated%22%3A%222026-02-18%2019%3A19%3A11.410Z%22%2C%22verified%22%3Atrue%7D%7D; Path=/; Expires=Thu, 19 Feb 2026 19:26:55 GMT; HttpOnly; Secure; SameSite=Strict";
-        let parsed = cookie_parse(&cookie.to_string()).unwrap();
-        println!("{:#?}", parsed);
+        assert!(cookie_value(cookie).unwrap().starts_with("{\"token\":\"eyJ"));
+        assert_eq!(cookie_value("theme=dark; pb_auth=\"a%20b\"").unwrap(), "a b");
+        assert!(cookie_value("theme=dark; Path=/; HttpOnly").is_none());
+        let mut auth_store = AuthStore::<DefaultAuthRecord>::default();
+        auth_store.load_from_cookie(cookie).unwrap();
+        assert_eq!(auth_store.collection_name.unwrap(), "_superusers");
+        assert_eq!(auth_store.record_id.unwrap(), "a69ulwk2mypsjzo");
+        assert_eq!(auth_store.record.as_ref().unwrap().created.unwrap().to_string(), "2026-01-28 10:02:31.589Z");
+        assert_eq!(auth_store.record.unwrap().email, "thomas@gysemans.dev");
+    }
+
+    #[test]
+    fn test_export_to_cookie() {
+        let data = fake_auth_data();
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        pb.load_auth_from_cookie(&format!("pb_auth={}", urlencoding::encode(&serde_json::to_string(&data).unwrap()))).unwrap();
+        let cookie = pb.auth_store().export_to_cookie(None);
+        assert!(cookie.starts_with("pb_auth="));
+        assert!(cookie.contains("; Path=/; Expires="));
+        assert!(cookie.ends_with(" GMT; HttpOnly; Secure; SameSite=Strict"));
+        let other = PocketBase::default("http://localhost:8091/").unwrap();
+        other.load_auth_from_cookie(&format!("theme=dark; {}", cookie.split(';').next().unwrap())).unwrap();
+        assert!(other.auth_store().is_valid());
+        assert_eq!(other.auth_store().token, Some(data.token));
+        assert_eq!(other.auth_store().record.unwrap().name.unwrap(), "John");
+        other.load_auth_from_cookie("theme=dark").unwrap();
+        assert!(!other.auth_store().is_some());
+        other.load_auth_from_cookie(&format!("theme=dark; {}", cookie.split(';').next().unwrap())).unwrap();
+        assert!(matches!(other.load_auth_from_cookie("pb_auth=%7Bnot%20json"), Err(ApiError::Json(_))));
+        assert!(!other.auth_store().is_some());
+        let options = CookieOptions { http_only: false, same_site: Some(SameSite::Lax), ..CookieOptions::default() };
+        let cookie = pb.auth_store().export_to_cookie(Some(options));
+        assert!(!cookie.contains("HttpOnly"));
+        assert!(cookie.ends_with("; Secure; SameSite=Lax"));
+    }
+
+    #[test]
+    fn test_export_big_cookie() {
+        let mut data = fake_auth_data();
+        data.record["bio"] = serde_json::Value::String("a".repeat(5000));
+        let mut auth_store = AuthStore::<serde_json::Value>::default();
+        auth_store.load_from_cookie(&format!("pb_auth={}", urlencoding::encode(&serde_json::to_string(&data).unwrap()))).unwrap();
+        assert!(auth_store.record.as_ref().unwrap().get("bio").is_some());
+        let cookie = auth_store.export_to_cookie(None);
+        assert!(cookie.len() <= 4096);
+        auth_store.load_from_cookie(&cookie).unwrap();
+        let record = auth_store.record.unwrap();
+        assert!(record.get("bio").is_none());
+        assert!(record.get("name").is_none());
+        assert_eq!(record["id"], "abc");
+        assert_eq!(record["email"], "john@example.com");
+        assert_eq!(record["collectionName"], "users");
+    }
+
+    #[test]
+    fn test_export_big_cookie_default_record() {
+        let mut data = fake_auth_data();
+        data.record["bio"] = serde_json::Value::String("a".repeat(5000));
+        let mut auth_store = AuthStore::<DefaultAuthRecord>::default();
+        auth_store.load_data(&data).unwrap();
+        let cookie = auth_store.export_to_cookie(None);
+        assert!(cookie.len() <= 4096);
+        let mut loaded_store = AuthStore::<DefaultAuthRecord>::default();
+        loaded_store.load_from_cookie(&cookie).unwrap();
+        let record = loaded_store.record.unwrap();
+        assert_eq!(record.id, "abc");
+        assert_eq!(record.email, "john@example.com");
+        assert!(record.verified);
+        assert!(record.name.is_none());
+        assert!(record.created.is_none());
+        // A record that can't be loaded clears the store.
+        let mut invalid = fake_auth_data();
+        invalid.record = serde_json::json!({ "id": "xyz" });
+        let invalid_cookie = format!("pb_auth={}", urlencoding::encode(&serde_json::to_string(&invalid).unwrap()));
+        auth_store.load_from_cookie(&invalid_cookie).unwrap_err();
+        assert!(!auth_store.is_some());
+        assert!(auth_store.token.is_none());
+    }
+
+    #[test]
+    fn test_drain_sse_events() {
+        let mut buffer = b"id:abc\nevent:PB_CONNECT\ndata:{\"clientId\":\"abc\"}\n\nid:def\nevent:articles/*\ndata:{\"action\"".to_vec();
+        let events = drain_sse_events(&mut buffer);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, "abc");
+        assert_eq!(events[0].event, "PB_CONNECT");
+        assert_eq!(events[0].data, "{\"clientId\":\"abc\"}");
+        buffer.extend(b":\"create\"}\n\n");
+        let events = drain_sse_events(&mut buffer);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "articles/*");
+        assert_eq!(events[0].data, "{\"action\":\"create\"}");
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_subscribe_to_collection() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let _ = pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await;
+        let demo = DEMO.data.articles[0].clone();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<RecordSubscription<ArticleRecord>>();
+        pb.collection("articles").subscribe("*", move |event: RecordSubscription<ArticleRecord>| {
+            let _ = tx.send(event);
+        }).await.expect("Could not subscribe to articles.");
+        assert!(pb.realtime().is_connected());
+        assert!(pb.realtime().client_id().is_some());
+        let _: ArticleRecord = pb.collection("articles").update(demo.id.clone(), ArticleUpdatePayload { name: demo.name.clone() }, None).await.expect("Could not update article.");
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv()).await.expect("No realtime event received.").unwrap();
+        assert_eq!(event.action, SubscriptionAction::Update);
+        assert_eq!(event.record.id, demo.id);
+        pb.collection("articles").unsubscribe_all().await.expect("Could not unsubscribe.");
+        assert!(!pb.realtime().is_connected());
+    }
+
+    #[test]
+    fn test_token_expiring() {
+        let token = fake_token(60, true);
+        assert!(!is_token_expiring(&token, Duration::ZERO));
+        assert!(!is_token_expiring(&token, Duration::from_secs(30)));
+        assert!(is_token_expiring(&token, Duration::from_secs(120)));
+        assert!(is_token_expiring(&token, Duration::MAX));
+        assert!(is_token_refreshable(&token));
+        assert!(!is_token_refreshable(&fake_token(60, false)));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_updates_auth_record() {
+        let mut data = fake_auth_data();
+        data.record["verified"] = serde_json::Value::Bool(false);
+        let backend = InMemoryBackend::default();
+        backend.save(&data).unwrap();
+        let pb: PocketBase = PocketBase::with_backend(mock_server(|_| String::new()), backend).unwrap();
+        pb.collection("users").confirm_verification(data.token.clone()).await.expect("Could not confirm verification.");
+        assert!(pb.auth_store().record.unwrap().verified);
+        let payload = "{\"collectionId\":\"_pb_users_auth_\",\"email\":\"john@example.com\",\"exp\":1769594551,\"id\":\"abc\",\"newEmail\":\"new@example.com\",\"type\":\"emailChange\"}";
+        let token = format!("{}.{}.signature", URL_SAFE_NO_PAD.encode("{\"alg\":\"HS256\",\"typ\":\"JWT\"}"), URL_SAFE_NO_PAD.encode(payload));
+        pb.collection("users").confirm_email_change(token, "password").await.expect("Could not confirm email change.");
+        assert!(!pb.auth_store().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_email_change_clears_auth_store() {
+        let data = fake_auth_data();
+        let path = std::env::temp_dir().join(format!("pbrsdk-email-change-{}.json", std::process::id()));
+        JsonFileBackend::new(&path).save(&data).unwrap();
+        let pb: PocketBase = PocketBase::with_backend(mock_server(|_| String::new()), JsonFileBackend::new(&path)).unwrap();
+        assert!(pb.auth_store().is_valid());
+        let payload = "{\"collectionId\":\"_pb_users_auth_\",\"email\":\"john@example.com\",\"exp\":1769594551,\"id\":\"abc\",\"newEmail\":\"new@example.com\",\"type\":\"emailChange\"}";
+        let token = format!("{}.{}.signature", URL_SAFE_NO_PAD.encode("{\"alg\":\"HS256\",\"typ\":\"JWT\"}"), URL_SAFE_NO_PAD.encode(payload));
+        pb.collection("users").confirm_email_change(token, "password").await.expect("Could not confirm email change.");
+        assert!(!pb.auth_store().is_valid());
+        assert!(pb.auth_store().record.is_none());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_auth_refresh() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let demo_user = &DEMO.data.users[0];
+        pb.collection("users").auth_with_password(&demo_user.email, &demo_user.password).await.expect("Could not authenticate user.");
+        let res = pb.collection("users").auth_refresh().await.expect("Could not refresh the token.");
+        let auth_store = pb.auth_store();
+        assert!(auth_store.is_valid());
+        assert_eq!(auth_store.token.unwrap(), res.token);
+        assert_eq!(auth_store.record.unwrap().id, demo_user.id);
+        assert_eq!(auth_store.record_id.unwrap(), demo_user.id);
+    }
+
+    #[test]
+    fn test_deserialize_auth_methods() {
+        let json = r#"{
+            "password": { "enabled": true, "identityFields": ["email"] },
+            "oauth2": { "enabled": true, "providers": [{
+                "name": "github",
+                "displayName": "GitHub",
+                "state": "abc",
+                "authURL": "https://github.com/login/oauth/authorize?client_id=1&redirect_uri=",
+                "codeVerifier": "verifier",
+                "codeChallenge": "challenge",
+                "codeChallengeMethod": "S256"
+            }] },
+            "mfa": { "enabled": false, "duration": 0 },
+            "otp": { "enabled": true, "duration": 180 }
+        }"#;
+        let methods = serde_json::from_str::<AuthMethodsList>(json).unwrap();
+        assert!(methods.password.enabled);
+        assert_eq!(methods.password.identity_fields, vec!["email"]);
+        assert_eq!(methods.oauth2.providers.len(), 1);
+        assert_eq!(methods.oauth2.providers[0].name, "github");
+        assert_eq!(methods.oauth2.providers[0].code_verifier, "verifier");
+        assert!(methods.oauth2.providers[0].auth_url.starts_with("https://github.com"));
+        assert!(!methods.mfa.enabled);
+        assert_eq!(methods.otp.duration, 180);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_list_auth_methods() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let methods = pb.collection("users").list_auth_methods().await.expect("Could not list the auth methods.");
+        assert!(methods.password.enabled);
+        assert!(methods.password.identity_fields.contains(&"email".to_string()));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_request_otp() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let demo_user = &DEMO.data.users[0];
+        let methods = pb.collection("users").list_auth_methods().await.expect("Could not list the auth methods.");
+        let response = pb.collection("users").request_otp(&demo_user.email).await;
+        if methods.otp.enabled {
+            assert!(!response.expect("Could not request an OTP.").is_empty());
+        } else {
+            assert!(response.is_err());
+        }
+        assert!(pb.collection("users").auth_with_otp("unknown", "12345678").await.is_err());
+        assert!(pb.auth_store().token.is_none());
+    }
+
+    #[test]
+    fn test_auth_options_query() {
+        assert_eq!(AuthOptions::default().to_url_query(), "");
+        assert_eq!(AuthOptions::mfa("abc 123").to_url_query(), "?mfaId=abc%20123");
+        let err = ApiError::MfaRequired { mfa_id: "abc".to_string() };
+        assert_eq!(err.to_string(), "Multi-factor authentication required (mfaId: abc)");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_account_lifecycle_requests() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let demo_user = &DEMO.data.users[0];
+        pb.collection("users").request_password_reset(&demo_user.email).await.expect("Could not request a password reset.");
+        pb.collection("users").request_verification(&demo_user.email).await.expect("Could not request a verification.");
+        assert!(pb.collection("users").confirm_password_reset("invalid", "password123", "password123").await.is_err());
+        assert!(pb.collection("users").confirm_verification("invalid").await.is_err());
+        assert!(pb.collection("users").request_email_change("new@example.com").await.is_err(), "Requesting an email change requires authentication.");
+        pb.collection("users").auth_with_password(&demo_user.email, &demo_user.password).await.expect("Could not authenticate user.");
+        assert!(pb.collection("users").confirm_email_change("invalid", &demo_user.password).await.is_err());
+        assert!(pb.auth_store().is_valid());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_impersonate() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let demo_user = &DEMO.data.users[0];
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        let impersonated = pb.collection("users").impersonate(&demo_user.id, Duration::from_secs(60)).await.expect("Could not impersonate user.");
+        let auth_store = impersonated.auth_store();
+        assert!(auth_store.is_valid());
+        assert!(!auth_store.is_superuser());
+        assert_eq!(auth_store.record_id.unwrap(), demo_user.id);
+        assert_eq!(auth_store.collection_name.unwrap(), "users");
+        assert!(!is_token_refreshable(auth_store.token.as_ref().unwrap()));
+        assert!(pb.auth_store().is_superuser());
+    }
+
+    #[test]
+    fn test_json_file_backend() {
+        let path = std::env::temp_dir().join(format!("pbrsdk-auth-{}.json", std::process::id()));
+        let backend = JsonFileBackend::new(&path);
+        assert!(backend.load().unwrap().is_none());
+        backend.save(&fake_auth_data()).unwrap();
+        #[cfg(unix)]
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&path).unwrap().permissions()) & 0o777, 0o600);
+        backend.save(&fake_auth_data()).unwrap();
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        assert!(!pb.auth_store().is_valid());
+        let pb = PocketBase::<DefaultAuthRecord>::with_backend("http://localhost:8091/", JsonFileBackend::new(&path)).unwrap();
+        let auth_store = pb.auth_store();
+        assert!(auth_store.is_valid());
+        assert_eq!(auth_store.record_id.unwrap(), "abc");
+        assert_eq!(auth_store.collection_name.unwrap(), "users");
+        assert_eq!(auth_store.record.unwrap().name.unwrap(), "John");
+        pb.clear_auth_store().unwrap();
+        assert!(!pb.auth_store().is_some());
+        assert!(!path.exists());
+        assert!(backend.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_json_file_backend_with_unloadable_data() {
+        let path = std::env::temp_dir().join(format!("pbrsdk-corrupted-auth-{}.json", std::process::id()));
+        std::fs::write(&path, "{not json").unwrap();
+        let pb = PocketBase::<DefaultAuthRecord>::with_backend("http://localhost:8091/", JsonFileBackend::new(&path)).unwrap();
+        assert!(!pb.auth_store().is_some());
+        assert!(!path.exists());
+        let mut data = fake_auth_data();
+        data.record["created"] = serde_json::Value::Bool(true);
+        JsonFileBackend::new(&path).save(&data).unwrap();
+        let pb = PocketBase::<DefaultAuthRecord>::with_backend("http://localhost:8091/", JsonFileBackend::new(&path)).unwrap();
+        assert!(!pb.auth_store().is_some());
+        assert!(!path.exists());
+    }
+
+    struct FailingBackend;
+
+    impl AuthStoreBackend for FailingBackend {
+        fn save(&self, _data: &AuthData) -> Result<(), ApiError> {
+            Err(std::io::Error::other("read-only").into())
+        }
+
+        fn load(&self) -> Result<Option<AuthData>, ApiError> {
+            Ok(None)
+        }
+
+        fn clear(&self) -> Result<(), ApiError> {
+            Err(std::io::Error::other("read-only").into())
+        }
+    }
+
+    #[test]
+    fn test_failing_backend() {
+        let data = fake_auth_data();
+        let cookie = format!("pb_auth={}", urlencoding::encode(&serde_json::to_string(&data).unwrap()));
+        let pb: PocketBase = PocketBase::with_backend("http://localhost:8091/", FailingBackend).unwrap();
+        let changes = Arc::new(std::sync::Mutex::new(0));
+        let changes_clone = changes.clone();
+        pb.on_auth_change(move |_| *changes_clone.lock().unwrap() += 1);
+        assert!(pb.load_auth_from_cookie(&cookie).is_err());
+        assert!(!pb.auth_store().is_some());
+        assert_eq!(*changes.lock().unwrap(), 0);
+    }
+
+    /// A backend whose `save` waits for the test to release it.
+    struct GatedBackend {
+        entered: std::sync::Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+        release: std::sync::Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl AuthStoreBackend for GatedBackend {
+        fn save(&self, _data: &AuthData) -> Result<(), ApiError> {
+            if let Some(entered) = self.entered.lock().unwrap().take() {
+                let _ = entered.send(());
+            }
+            let _ = self.release.lock().unwrap().recv();
+            Ok(())
+        }
+
+        fn load(&self) -> Result<Option<AuthData>, ApiError> {
+            Ok(None)
+        }
+
+        fn clear(&self) -> Result<(), ApiError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_save_without_locking_auth_store() {
+        let (entered_sender, entered) = tokio::sync::oneshot::channel();
+        let (release, release_receiver) = std::sync::mpsc::channel();
+        let backend = GatedBackend {
+            entered: std::sync::Mutex::new(Some(entered_sender)),
+            release: std::sync::Mutex::new(release_receiver),
+        };
+        let pb: PocketBase = PocketBase::with_backend(mock_server(|_| serde_json::to_string(&fake_auth_data()).unwrap()), backend).unwrap();
+        let (auth, _) = tokio::join!(
+            async { pb.collection("users").auth_with_password("john@example.com", "password").await },
+            async {
+                entered.await.unwrap();
+                assert!(!pb.auth_store().is_some());
+                release.send(()).unwrap();
+            },
+        );
+        auth.expect("Could not authenticate.");
+        assert!(pb.auth_store().is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_on_auth_change() {
+        let data = fake_auth_data();
+        let cookie = format!("pb_auth={}", urlencoding::encode(&serde_json::to_string(&data).unwrap()));
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let changes = Arc::new(std::sync::Mutex::new(Vec::<bool>::new()));
+        let changes_clone = changes.clone();
+        let id = pb.on_auth_change(move |auth_store| changes_clone.lock().unwrap().push(auth_store.is_valid()));
+        let mut receiver = pb.watch_auth();
+        assert!(!receiver.borrow().is_some());
+        pb.load_auth_from_cookie(&cookie).unwrap();
+        receiver.changed().await.unwrap();
+        assert_eq!(receiver.borrow_and_update().record_id.as_ref().unwrap(), "abc");
+        pb.clear_auth_store().unwrap();
+        receiver.changed().await.unwrap();
+        assert!(!receiver.borrow_and_update().is_some());
+        assert_eq!(*changes.lock().unwrap(), vec![true, false]);
+        pb.remove_auth_listener(id);
+        pb.load_auth_from_cookie(&cookie).unwrap();
+        assert_eq!(changes.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_deserialize_collections() {
+        let collections = serde_json::from_str::<Vec<CollectionModel>>(include_str!("../pb_schema.json")).expect("Could not parse pb_schema.json");
+        let articles = collections.iter().find(|x| x.name == "articles").expect("Missing articles collection");
+        assert_eq!(articles.collection_type, CollectionType::Base);
+        assert!(!articles.system);
+        assert!(articles.options.is_empty());
+        let users = collections.iter().find(|x| x.name == "users").expect("Missing users collection");
+        assert_eq!(users.collection_type, CollectionType::Auth);
+        assert!(users.options.contains_key("passwordAuth"));
+        assert!(users.options.contains_key("authRule"));
+        let serialized = serde_json::to_value(users).unwrap();
+        assert_eq!(serialized["type"], "auth");
+        assert!(serialized["passwordAuth"]["enabled"].as_bool().unwrap());
+        assert!(serialized.get("options").is_none());
+        let new_collection = serde_json::to_value(CollectionModel::new("posts", CollectionType::Base)).unwrap();
+        assert!(new_collection.get("id").is_none());
+        assert!(new_collection["listRule"].is_null());
+        assert!(new_collection.get("created").is_none());
+        let posts = serde_json::from_value::<CollectionModel>(serde_json::json!({ "name": "posts", "type": "base", "created": "2026-01-28 10:02:31.589Z", "updated": "" })).unwrap();
+        assert_eq!(posts.created.unwrap().to_string(), "2026-01-28 10:02:31.589Z");
+        assert!(posts.updated.is_none());
+    }
+
+    #[test]
+    fn test_deserialize_fields() {
+        let collections = serde_json::from_str::<Vec<CollectionModel>>(include_str!("../pb_schema.json")).expect("Could not parse pb_schema.json");
+        assert!(collections.iter().flat_map(|x| &x.fields).all(|x| !matches!(x, Field::Unknown(_))));
+        let articles = collections.iter().find(|x| x.name == "articles").expect("Missing articles collection");
+        let Some(Field::Text(id)) = articles.fields.iter().find(|x| x.name() == "id") else { panic!("Missing id field") };
+        assert!(id.primary_key);
+        assert!(id.base.system);
+        assert_eq!(id.pattern, "^[a-z0-9]+$");
+        assert!(articles.fields.iter().any(|x| matches!(x, Field::Autodate(f) if f.on_create)));
+        let users = collections.iter().find(|x| x.name == "users").expect("Missing users collection");
+        assert!(users.fields.iter().any(|x| x.type_name() == "password"));
+        let reparsed = serde_json::from_value::<Vec<CollectionModel>>(serde_json::to_value(&collections).unwrap()).unwrap();
+        assert_eq!(reparsed, collections);
+
+        let unknown = serde_json::from_str::<Field>(r#"{"type":"custom","name":"foo","id":"abc","extra":1}"#).unwrap();
+        assert!(matches!(unknown, Field::Unknown(_)));
+        assert_eq!(unknown.type_name(), "custom");
+        assert_eq!(unknown.name(), "foo");
+        assert_eq!(serde_json::to_value(&unknown).unwrap()["extra"], 1);
+
+        let Field::Text(text) = serde_json::from_str::<Field>(r#"{"type":"text","name":"title","max":10,"newOption":true}"#).unwrap() else { panic!("Not a text field") };
+        assert_eq!(text.max, 10);
+        assert_eq!(text.extra.len(), 1);
+        assert_eq!(serde_json::to_value(Field::Text(text)).unwrap()["newOption"], true);
+
+        let relation = Field::Relation(RelationField {
+            base: FieldBase::new("author"),
+            collection_id: "_pb_users_auth_".to_string(),
+            cascade_delete: true,
+            max_select: 1,
+            ..RelationField::default()
+        });
+        let serialized = serde_json::to_value(&relation).unwrap();
+        assert_eq!(serialized["type"], "relation");
+        assert_eq!(serialized["collectionId"], "_pb_users_auth_");
+        assert!(serialized["cascadeDelete"].as_bool().unwrap());
+        assert!(serialized.get("id").is_none());
     }
-}
\ No newline at end of file
+
+    #[base_system_fields(crate = crate)]
+    struct ProductRecord {
+        image: String,
+    }
+
+    #[test]
+    fn test_file_urls() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        let record = ProductRecord {
+            id: "abc".to_string(),
+            collection_id: "pbc_123".to_string(),
+            collection_name: "products".to_string(),
+            image: "my image.png".to_string(),
+        };
+        assert_eq!(pb.files().get_url(&record, &record.image, None), "http://localhost:8091/api/files/pbc_123/abc/my%20image.png");
+        let options = FileOptions { thumb: Some("100x100".to_string()), download: true, token: Some("tok".to_string()) };
+        assert_eq!(pb.files().get_url(&record, "a.png", Some(options)), "http://localhost:8091/api/files/pbc_123/abc/a.png?thumb=100x100&download=1&token=tok");
+        assert_eq!(pb.files().get_url(&record, "", None), "");
+        let raw = serde_json::json!({ "id": "abc", "collectionName": "products" });
+        assert_eq!(pb.files().get_url(&raw, "a.png", None), "http://localhost:8091/api/files/products/abc/a.png");
+    }
+
+    #[test]
+    fn test_filter_builder() {
+        use crate::filter::field;
+        assert_eq!(field("name").eq("it's").to_string(), r"name = 'it\'s'");
+        assert_eq!(field("price").gte(10.5).to_string(), "price >= 10.5");
+        assert_eq!(field("public").ne(false).to_string(), "public != false");
+        assert_eq!(field("author").eq(None::<String>).to_string(), "author = null");
+        assert_eq!(field("author").is_not_null().to_string(), "author != null");
+        assert_eq!(field("tags").any_eq(vec!["a", "b'"]).to_string(), r#"tags ?= '["a","b\'"]'"#);
+        assert_eq!(field("created").lt(UNIX_EPOCH + Duration::from_millis(1769594551589)).to_string(), "created < '2026-01-28 10:02:31.589Z'");
+        assert_eq!(field("name").not_like("%x").to_string(), "name !~ '%x'");
+        assert_eq!(field("author").eq(FilterValue::raw("@request.auth.id")).to_string(), "author = @request.auth.id");
+        let filter = field("a").eq(1).and(field("b").eq(2)).and(field("c").eq(3).or(field("d").eq(4)));
+        assert_eq!(filter.to_string(), "a = 1 && b = 2 && (c = 3 || d = 4)");
+        let filter = field("a").eq(1).or(field("b").eq(2).and(Filter::raw("c = 3")));
+        assert_eq!(filter.to_string(), "a = 1 || (b = 2 && (c = 3))");
+        let options = ListOptions { filter: filter.into(), ..ListOptions::default() };
+        assert!(options.filter.is_some());
+    }
+
+    #[test]
+    fn test_filter_bind() {
+        use crate::filter::bind;
+        let raw = bind("a = {:a} && b = {:b} && c = {:c} && d = {:d} && a != {:a}", serde_json::json!({ "a": "x'y", "b": 1.5, "c": null, "d": true }));
+        assert_eq!(raw, r"a = 'x\'y' && b = 1.5 && c = null && d = true && a != 'x\'y'");
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        assert_eq!(pb.filter("id = {:id} && {:missing}", serde_json::json!({ "id": "abc" })), "id = 'abc' && {:missing}");
+        let raw = bind("x = {:a}", serde_json::json!({ "a": "{:b}", "b": " || id != " }));
+        assert_eq!(raw, "x = '{:b}'");
+        assert_eq!(bind("{:a} {: {:a}} {:b", serde_json::json!({ "a": 1 })), "1 {: 1} {:b");
+        let raw = bind("name = {:a} && title = {:b}", serde_json::json!({ "a": "\\", "b": " || id != " }));
+        assert_eq!(raw, r"name = '\\' && title = ' || id != '");
+        assert_eq!(crate::filter::field("name").eq(r"a\'b").to_string(), r"name = 'a\\\'b'");
+    }
+
+    #[test]
+    fn test_pb_datetime() {
+        use crate::filter::field;
+        let date: PbDateTime = "2026-01-28 10:02:31.589Z".parse().unwrap();
+        assert_eq!(date.unix_millis(), 1769594551589);
+        assert_eq!(date.to_string(), "2026-01-28 10:02:31.589Z");
+        assert_eq!(SystemTime::from(date), UNIX_EPOCH + Duration::from_millis(1769594551589));
+        assert_eq!("2026-01-28T10:02:31Z".parse::<PbDateTime>().unwrap().to_string(), "2026-01-28 10:02:31.000Z");
+        assert_eq!("2024-02-29 00:00:00.5Z".parse::<PbDateTime>().unwrap().to_string(), "2024-02-29 00:00:00.500Z");
+        assert_eq!(PbDateTime::from_unix_millis(-1).to_string(), "1969-12-31 23:59:59.999Z");
+        assert_eq!(PbDateTime::from(UNIX_EPOCH - Duration::from_micros(500)).to_string(), "1969-12-31 23:59:59.999Z");
+        assert_eq!(PbDateTime::from(UNIX_EPOCH - Duration::from_millis(1)).unix_millis(), -1);
+        assert_eq!(PbDateTime::from(UNIX_EPOCH + Duration::from_micros(500)).unix_millis(), 0);
+        for invalid in ["", "2026-01-28", "2025-02-29 00:00:00Z", "2026-13-01 00:00:00Z", "2026-01-28 10:02:31.Z", "2026-01-28 10:02:31+02:00"] {
+            assert!(invalid.parse::<PbDateTime>().is_err(), "{}", invalid);
+        }
+        assert_eq!(field("created").gte(date).to_string(), "created >= '2026-01-28 10:02:31.589Z'");
+        assert_eq!(serde_json::to_value(date).unwrap(), serde_json::json!("2026-01-28 10:02:31.589Z"));
+        assert_eq!(serde_json::from_value::<PbDateTime>(serde_json::json!("2026-01-28 10:02:31.589Z")).unwrap(), date);
+        assert!(serde_json::from_value::<PbDateTime>(serde_json::json!("")).is_err());
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Event {
+            #[serde(default, with = "crate::datetime::optional")]
+            starts_at: Option<PbDateTime>,
+        }
+        assert_eq!(serde_json::from_str::<Event>(r#"{"starts_at":""}"#).unwrap(), Event { starts_at: None });
+        assert_eq!(serde_json::from_str::<Event>(r#"{}"#).unwrap(), Event { starts_at: None });
+        assert_eq!(serde_json::from_str::<Event>(r#"{"starts_at":"2026-01-28 10:02:31.589Z"}"#).unwrap(), Event { starts_at: Some(date) });
+        assert_eq!(serde_json::to_string(&Event { starts_at: None }).unwrap(), r#"{"starts_at":""}"#);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_pb_datetime_chrono() {
+        let date: PbDateTime = "2026-01-28 10:02:31.589Z".parse().unwrap();
+        let chrono_date = chrono::DateTime::<chrono::Utc>::try_from(date).unwrap();
+        assert_eq!(chrono_date.to_rfc3339(), "2026-01-28T10:02:31.589+00:00");
+        assert_eq!(PbDateTime::from(chrono_date), date);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_pb_datetime_time() {
+        let date: PbDateTime = "2026-01-28 10:02:31.589Z".parse().unwrap();
+        let time_date = time::OffsetDateTime::try_from(date).unwrap();
+        assert_eq!(time_date.unix_timestamp(), 1769594551);
+        assert_eq!(time_date.millisecond(), 589);
+        assert_eq!(PbDateTime::from(time_date), date);
+        let before_epoch = time::OffsetDateTime::UNIX_EPOCH - time::Duration::microseconds(500);
+        assert_eq!(PbDateTime::from(before_epoch).to_string(), "1969-12-31 23:59:59.999Z");
+    }
+
+    #[test]
+    fn test_sort_and_fields_builders() {
+        assert_eq!(Sort::asc("name").to_string(), "name");
+        assert_eq!(Sort::random().to_string(), "@random");
+        assert_eq!(Sort::desc("created").then_asc("name").then_random().to_string(), "-created,name,@random");
+        assert_eq!(Sort::asc(Sort::ROWID).to_string(), "@rowid");
+        let fields = Fields::from_iter(["id", "name"]).excerpt("bio", 50, false).expand("author.company", ["name", "id"]);
+        assert_eq!(fields.to_string(), "id,name,bio:excerpt(50,false),expand.author.expand.company.name,expand.author.expand.company.id");
+        assert_eq!(Option::<String>::from(Fields::new()), None);
+        let options = ViewOptions { fields: Fields::new().field("id").into(), sort: Sort::desc("created").into(), expand: None };
+        assert_eq!(options.to_url_query(), "?fields=id&sort=-created");
+        let options = ListOptions { fields: Fields::new().field("*").into(), sort: Sort::desc(Sort::ROWID).into(), ..ListOptions::default() };
+        assert_eq!(options.to_url_query_with(&[]), "?fields=%2A&sort=-%40rowid");
+    }
+
+    #[test]
+    fn test_geo_point() {
+        use crate::filter::{field, geo_distance};
+        let store = GeoPoint::new(-0.5792, 44.8378).unwrap();
+        assert_eq!((store.lon(), store.lat()), (-0.5792, 44.8378));
+        assert_eq!(serde_json::to_value(store).unwrap(), serde_json::json!({ "lon": -0.5792, "lat": 44.8378 }));
+        assert_eq!(serde_json::from_value::<GeoPoint>(serde_json::json!({ "lon": 0, "lat": 0 })).unwrap(), GeoPoint::default());
+        let filter = field("location").within_km(store, 5.0).and(field("open").eq(true));
+        assert_eq!(filter.to_string(), "geoDistance(location.lon, location.lat, -0.5792, 44.8378) <= 5.0 && open = true");
+        assert_eq!(geo_distance("address.location", store).gt(1).to_string(), "geoDistance(address.location.lon, address.location.lat, -0.5792, 44.8378) > 1");
+        let sort = Sort::desc("rating").then_nearest("location", store);
+        assert_eq!(sort.to_string(), "-rating,geoDistance(location.lon, location.lat, -0.5792, 44.8378)");
+        assert_eq!(Sort::farthest("location", GeoPoint::new(1.0, 2.5).unwrap()).to_string(), "-geoDistance(location.lon, location.lat, 1.0, 2.5)");
+        assert!(GeoPoint::new(f64::NAN, 44.8378).is_err());
+        assert!(GeoPoint::new(-0.5792, f64::NEG_INFINITY).is_err());
+        assert_eq!(GeoPoint::new(f64::INFINITY, 1.0).unwrap_err().to_string(), "Invalid coordinates: (inf, 1)");
+        assert!(GeoPoint::new(180.0, -90.0).is_ok());
+        assert!(GeoPoint::new(180.5, 0.0).is_err());
+        assert!(GeoPoint::new(0.0, -90.5).is_err());
+        assert!(serde_json::from_value::<GeoPoint>(serde_json::json!({ "lon": 44.8, "lat": -120.0 })).is_err());
+    }
+
+    #[base_system_fields(crate = crate)]
+    #[derive(Debug, Clone)]
+    struct CompanyRecord {
+        name: String,
+    }
+
+    #[base_system_fields(crate = crate)]
+    #[expand(company: CompanyRecord)]
+    #[derive(Debug, Clone)]
+    struct AuthorRecord {
+        name: String,
+    }
+
+    #[base_system_fields(crate = crate)]
+    #[expand(author: AuthorRecord, tags: Vec<CompanyRecord>)]
+    #[derive(Debug, Clone)]
+    struct PostRecord {
+        title: String,
+        author: String,
+    }
+
+    #[base_system_fields(crate = crate)]
+    #[expand(parent: Box<CategoryRecord>, children: Vec<CategoryRecord>)]
+    #[derive(Debug, Clone)]
+    struct CategoryRecord {
+        name: String,
+    }
+
+    #[test]
+    fn test_typed_expand() {
+        assert_eq!(CompanyRecord::expand_query(), "");
+        assert_eq!(AuthorRecord::expand_query(), "company");
+        assert_eq!(PostRecord::expand_query(), "author,author.company,tags");
+        assert_eq!(ViewOptions::expanded::<PostRecord>().to_url_query(), "?expand=author%2Cauthor.company%2Ctags");
+        assert!(ListOptions::expanded::<CompanyRecord>().expand.is_none());
+        let post: PostRecord = serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "collectionId": "pbc_posts",
+            "collectionName": "posts",
+            "title": "Hello",
+            "author": "a1",
+            "expand": {
+                "author": {
+                    "id": "a1",
+                    "collectionId": "pbc_authors",
+                    "collectionName": "authors",
+                    "name": "John",
+                    "expand": {
+                        "company": { "id": "c1", "collectionId": "pbc_companies", "collectionName": "companies", "name": "ACME" }
+                    }
+                },
+                "tags": [
+                    { "id": "t1", "collectionId": "pbc_companies", "collectionName": "companies", "name": "First" },
+                    { "id": "t2", "collectionId": "pbc_companies", "collectionName": "companies", "name": "Second" }
+                ]
+            }
+        })).unwrap();
+        let author = post.expand.author.get().expect("The author should be expanded.");
+        assert_eq!(author.id, post.author);
+        assert_eq!(author.name, "John");
+        assert_eq!(author.expand.company.get().unwrap().name, "ACME");
+        assert_eq!(post.expand.tags.get().unwrap().len(), 2);
+        let post: PostRecord = serde_json::from_value(serde_json::json!({
+            "id": "p1", "collectionId": "pbc_posts", "collectionName": "posts", "title": "Hello", "author": ""
+        })).unwrap();
+        assert_eq!(post.title, "Hello");
+        assert!(!post.expand.author.is_expanded());
+        assert!(post.expand.tags.clone().into_inner().is_none());
+    }
+
+    #[test]
+    fn test_typed_expand_self_relation() {
+        assert_eq!(CategoryRecord::expand_paths(), ["parent", "children"]);
+        assert_eq!(<Vec<CategoryRecord>>::expand_paths().len(), 2);
+        let category: CategoryRecord = serde_json::from_value(serde_json::json!({
+            "id": "c2", "collectionId": "pbc_categories", "collectionName": "categories", "name": "Child",
+            "expand": {
+                "parent": { "id": "c1", "collectionId": "pbc_categories", "collectionName": "categories", "name": "Root" }
+            }
+        })).unwrap();
+        let parent = category.expand.parent.get().expect("The parent should be expanded.");
+        assert_eq!(parent.name, "Root");
+        assert!(!parent.expand.parent.is_expanded());
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let remote = CollectionModel::from_file("pb_schema.json").unwrap();
+        assert!(schema::diff(&remote, &remote).is_empty());
+        assert_eq!(schema::diff(&remote, &remote).to_string(), "No changes.");
+        let mut local = remote.clone();
+        let articles = local.iter_mut().find(|x| x.name == "articles").unwrap();
+        articles.name = "posts".to_string();
+        articles.list_rule = None;
+        articles.indexes.push("CREATE INDEX idx_title ON posts (title)".to_string());
+        articles.fields.retain(|x| x.name() != "public");
+        if let Some(Field::Number(price)) = articles.fields.iter_mut().find(|x| x.type_name() == "number") {
+            price.only_int = true;
+        }
+        articles.fields.push(Field::Bool(BoolField { base: FieldBase::new("published"), ..BoolField::default() }));
+        let mut comments = CollectionModel::new("comments", CollectionType::Base);
+        comments.fields.push(Field::Text(TextField { base: FieldBase::new("body"), ..TextField::default() }));
+        local.push(comments);
+        local.retain(|x| x.name != "users");
+        let diff = schema::diff(&local, &remote);
+        assert!(diff.changes.contains(&SchemaChange::CollectionRenamed { from: "articles".to_string(), to: "posts".to_string() }));
+        assert!(diff.changes.contains(&SchemaChange::CollectionAdded { name: "comments".to_string() }));
+        assert!(diff.changes.contains(&SchemaChange::CollectionRemoved { name: "users".to_string() }));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::RuleChanged { rule: RuleKind::List, after: None, .. })));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::FieldAdded { field, .. } if field.name() == "published")));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::FieldRemoved { field, .. } if field.name() == "public")));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::FieldChanged { after, .. } if after.type_name() == "number")));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::IndexAdded { collection, .. } if collection == "posts")));
+        let report = diff.to_string();
+        assert!(report.contains("~ collection articles renamed to posts"));
+        assert!(report.contains("~ posts.listRule: \"\" -> null"));
+        assert!(report.contains("onlyInt false -> true"));
+        assert!(report.contains("- collection users"));
+    }
+
+    #[test]
+    fn test_schema_diff_collection_options() {
+        let remote = CollectionModel::from_file("pb_schema.json").unwrap();
+        let mut local = remote.clone();
+        let articles = local.iter_mut().find(|x| x.name == "articles").unwrap();
+        articles.collection_type = CollectionType::View;
+        articles.view_query = Some("SELECT id, name FROM products".to_string());
+        let users = local.iter_mut().find(|x| x.name == "users").unwrap();
+        users.options["passwordAuth"]["enabled"] = serde_json::Value::Bool(false);
+        users.options.insert("authRule".to_string(), serde_json::Value::String("verified = true".to_string()));
+        users.options.insert("custom".to_string(), serde_json::Value::Bool(true));
+        let diff = schema::diff(&local, &remote);
+        assert!(diff.changes.contains(&SchemaChange::TypeChanged { collection: "articles".to_string(), before: CollectionType::Base, after: CollectionType::View }));
+        assert!(diff.changes.contains(&SchemaChange::ViewQueryChanged { collection: "articles".to_string(), before: None, after: Some("SELECT id, name FROM products".to_string()) }));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::OptionChanged { key, after: Some(after), .. } if key == "passwordAuth" && after["enabled"] == false)));
+        assert!(diff.changes.contains(&SchemaChange::OptionChanged { collection: "users".to_string(), key: "custom".to_string(), before: None, after: Some(serde_json::Value::Bool(true)) }));
+        assert!(diff.changes.iter().any(|x| matches!(x, SchemaChange::RuleChanged { rule: RuleKind::Auth, .. })));
+        assert!(!diff.changes.iter().any(|x| matches!(x, SchemaChange::OptionChanged { key, .. } if key == "authRule")));
+        let report = diff.to_string();
+        assert!(report.contains("~ articles.type: base -> view"));
+        assert!(report.contains("~ articles.viewQuery: null -> \"SELECT id, name FROM products\""));
+        assert!(report.contains("~ users.custom: null -> true"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_collections_crud() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        assert!(pb.collections().get_full_list().await.is_err(), "Listing collections requires a superuser.");
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        let collections = pb.collections().get_full_list().await.expect("Could not fetch collections.");
+        assert!(collections.iter().any(|x| x.name == "articles"));
+        let articles = pb.collections().get_one("articles", None).await.expect("Could not fetch articles collection.");
+        assert_eq!(articles.name, "articles");
+        let first = pb.collections().get_first_list_item("name='users'", None).await.expect("Could not fetch users collection.");
+        assert_eq!(first.collection_type, CollectionType::Auth);
+        let mut model = CollectionModel::new("pbrsdk_tmp", CollectionType::Base);
+        model.list_rule = Some(String::new());
+        let created = pb.collections().create(&model).await.expect("Could not create collection.");
+        assert!(!created.id.is_empty());
+        assert_eq!(created.list_rule, Some(String::new()));
+        let updated = pb.collections().update(&created.id, serde_json::json!({ "listRule": null })).await.expect("Could not update collection.");
+        assert!(updated.list_rule.is_none());
+        pb.collections().truncate(&created.id).await.expect("Could not truncate collection.");
+        pb.collections().delete(&created.id).await.expect("Could not delete collection.");
+        assert!(pb.collections().get_one(&created.id, None).await.is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_import_collections() {
+        let pb = PocketBase::default("http://localhost:8091/").unwrap();
+        pb.collection("_superusers").auth_with_password("thomas@gysemans.dev", "thomasgysemans").await.expect("Could not authenticate super user.");
+        pb.collections().import_file("pb_schema.json", false).await.expect("Could not import pb_schema.json");
+        let mut collections = CollectionModel::from_file("pb_schema.json").unwrap();
+        let mut model = CollectionModel::new("pbrsdk_imported", CollectionType::Base);
+        model.id = "pbc_pbrsdk_imported".to_string();
+        collections.push(model);
+        pb.collections().import(&collections, false).await.expect("Could not import collections.");
+        let imported = pb.collections().get_one("pbrsdk_imported", None).await.expect("Could not fetch imported collection.");
+        assert_eq!(imported.id, "pbc_pbrsdk_imported");
+        pb.collections().delete(&imported.id).await.expect("Could not delete collection.");
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::path::PathBuf;
+use reqwest::Body;
+use reqwest::multipart::{Form, Part};
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+use crate::error::ApiError;
+
+/// The name of the multipart field holding the regular fields of the record.
+const JSON_PAYLOAD_KEY: &str = "@jsonPayload";
+
+enum FileSource {
+    Bytes(Vec<u8>),
+    Path(PathBuf),
+    Reader(Box<dyn AsyncRead + Send + Unpin>),
+}
+
+/// A file to upload into a `file` field of a record.
+///
+/// PocketBase detects the MIME type of the file from its content,
+/// so setting it with [FileUpload::mime_type] is optional.
+pub struct FileUpload {
+    source: FileSource,
+    file_name: String,
+    mime_type: Option<String>,
+}
+
+impl fmt::Debug for FileUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match &self.source {
+            FileSource::Bytes(bytes) => format!("{} bytes", bytes.len()),
+            FileSource::Path(path) => path.display().to_string(),
+            FileSource::Reader(_) => "reader".to_string(),
+        };
+        f.debug_struct("FileUpload")
+            .field("source", &source)
+            .field("file_name", &self.file_name)
+            .field("mime_type", &self.mime_type)
+            .finish()
+    }
+}
+
+impl FileUpload {
+    /// A file whose content is already in memory.
+    pub fn from_bytes(file_name: impl Into<String>, bytes: impl Into<Vec<u8>>) -> Self {
+        FileUpload {
+            source: FileSource::Bytes(bytes.into()),
+            file_name: file_name.into(),
+            mime_type: None,
+        }
+    }
+
+    /// A file read from the disk when the request is sent.
+    /// Its name is the name of the file on the disk.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let file_name = path.file_name().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default();
+        FileUpload {
+            source: FileSource::Path(path),
+            file_name,
+            mime_type: None,
+        }
+    }
+
+    /// A file streamed from an async reader, without being loaded in memory.
+    pub fn from_reader(file_name: impl Into<String>, reader: impl AsyncRead + Send + Unpin + 'static) -> Self {
+        FileUpload {
+            source: FileSource::Reader(Box::new(reader)),
+            file_name: file_name.into(),
+            mime_type: None,
+        }
+    }
+
+    /// Overrides the name of the file.
+    /// PocketBase appends a random suffix to it anyway.
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Sets the MIME type of the file, such as `image/png`.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    async fn into_part(self) -> Result<Part, ApiError> {
+        let part = match self.source {
+            FileSource::Bytes(bytes) => Part::bytes(bytes),
+            FileSource::Path(path) => {
+                let file = tokio::fs::File::open(path).await?;
+                let length = file.metadata().await?.len();
+                Part::stream_with_length(Body::wrap_stream(ReaderStream::new(file)), length)
+            }
+            FileSource::Reader(reader) => Part::stream(Body::wrap_stream(ReaderStream::new(reader))),
+        };
+        let part = part.file_name(self.file_name);
+        match self.mime_type {
+            Some(mime_type) => Ok(part.mime_str(&mime_type)?),
+            None => Ok(part),
+        }
+    }
+}
+
+/// The files sent along with the body of a record
+/// by [RecordService::create_with_files](crate::RecordService::create_with_files)
+/// and [RecordService::update_with_files](crate::RecordService::update_with_files).
+///
+/// ```rust,no_run
+/// # use pbrsdk::*;
+/// let files = RecordFiles::new()
+///     .set("avatar", FileUpload::from_path("avatar.png"))
+///     .append("documents", FileUpload::from_bytes("notes.txt", "Hello"))
+///     .remove("documents", ["old_1a2b3c4d5e.txt"]);
+/// ```
+#[derive(Debug, Default)]
+pub struct RecordFiles {
+    files: Vec<(String, FileUpload)>,
+    removed: Vec<(String, Vec<String>)>,
+}
+
+impl RecordFiles {
+    /// Creates an empty set of files.
+    pub fn new() -> Self {
+        RecordFiles::default()
+    }
+
+    /// Adds a file to the field, replacing its current files.
+    /// Call it several times for the same field to upload several files.
+    pub fn set(mut self, field: impl Into<String>, file: FileUpload) -> Self {
+        self.files.push((field.into(), file));
+        self
+    }
+
+    /// Adds a file after the current files of a multi-file field (`field+`).
+    pub fn append(mut self, field: impl Into<String>, file: FileUpload) -> Self {
+        self.files.push((format!("{}+", field.into()), file));
+        self
+    }
+
+    /// Adds a file before the current files of a multi-file field (`+field`).
+    pub fn prepend(mut self, field: impl Into<String>, file: FileUpload) -> Self {
+        self.files.push((format!("+{}", field.into()), file));
+        self
+    }
+
+    /// Removes existing files from a multi-file field by their name (`field-`).
+    /// Calling it several times for the same field removes all the given files.
+    pub fn remove<I, S>(mut self, field: impl Into<String>, file_names: I) -> Self
+    where I: IntoIterator<Item = S>, S: Into<String> {
+        let key = format!("{}-", field.into());
+        let file_names = file_names.into_iter().map(|x| x.into());
+        match self.removed.iter_mut().find(|(x, _)| *x == key) {
+            Some((_, names)) => names.extend(file_names),
+            None => self.removed.push((key, file_names.collect())),
+        }
+        self
+    }
+
+    /// Whether there is no file to upload nor to remove.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.removed.is_empty()
+    }
+
+    /// Builds the JSON payload: the body along with the names of the removed files.
+    /// The names are merged with the ones the body may already remove from the same field.
+    /// Fails if the body isn't serialized as a JSON object (or null).
+    pub(crate) fn payload<S: Serialize>(&mut self, body: S) -> Result<serde_json::Value, ApiError> {
+        let mut payload = serde_json::to_value(body)?;
+        if payload.is_null() {
+            payload = serde_json::Value::Object(serde_json::Map::new());
+        }
+        let Some(object) = payload.as_object_mut() else {
+            return Err(ApiError::Json(serde::ser::Error::custom("the body of a record must be serialized as a JSON object")));
+        };
+        for (key, names) in std::mem::take(&mut self.removed) {
+            let mut merged = match object.remove(&key) {
+                None | Some(serde_json::Value::Null) => Vec::new(),
+                Some(serde_json::Value::String(name)) => vec![serde_json::Value::String(name)],
+                Some(serde_json::Value::Array(existing)) => existing,
+                Some(_) => return Err(ApiError::Json(serde::ser::Error::custom(format!("`{key}` must be a file name or an array of file names")))),
+            };
+            merged.extend(names.into_iter().map(serde_json::Value::String));
+            object.insert(key, serde_json::Value::Array(merged));
+        }
+        Ok(payload)
+    }
+
+    /// Builds the multipart form. The [payload](Self::payload) is serialized as JSON into the `@jsonPayload` field.
+    pub(crate) async fn into_form<S: Serialize>(mut self, body: S) -> Result<Form, ApiError> {
+        let payload = self.payload(body)?;
+        let mut form = Form::new().text(JSON_PAYLOAD_KEY, payload.to_string());
+        for (key, file) in self.files {
+            form = form.part(key, file.into_part().await?);
+        }
+        Ok(form)
+    }
+}